@@ -0,0 +1,259 @@
+use crate::api::catalogue::{
+    collect_catalogue, numbers, StationCatalogue, StationQuery, CATALOGUE_WORKERS,
+};
+use crate::api::config::StationConfig;
+use crate::api::discovery::resolve_api_urls;
+use crate::api::network::{assemble, Network, NETWORK_WORKERS};
+use crate::api::requests::{self, ApiRequest};
+use crate::api::structs::*;
+use crate::api::template::UrlTemplate;
+use crate::async_client::AsyncHisparcClient;
+use crate::error::{Context, HisparcError, Result};
+use chrono::NaiveDate;
+use futures_util::{stream, StreamExt, TryStreamExt};
+use serde::de::DeserializeOwned;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+impl AsyncHisparcClient {
+    async fn get_api_urls_internal(&self) -> Result<HashMap<String, UrlTemplate>> {
+        let api_urls = self
+            .get_json::<HashMap<String, String>>(&self.api_url)
+            .await?;
+
+        resolve_api_urls(&self.api_url, api_urls)
+    }
+
+    async fn api_urls(&self) -> Arc<HashMap<String, UrlTemplate>> {
+        if let Some(urls) = self.api_urls.current() {
+            return urls;
+        }
+
+        match self.get_api_urls_internal().await {
+            Ok(urls) => self.api_urls.store_discovered(urls),
+            Err(_) => self.api_urls.store_fallback(&self.api_url),
+        }
+    }
+
+    // Falls back to the known endpoint templates when the API root cannot be
+    // reached, and tries discovery again after the client's discovery retry
+    // interval.
+    pub async fn get_api_url(&self, key: &str) -> Result<String> {
+        Ok(self.get_api_template(key).await?.as_str().to_string())
+    }
+
+    pub async fn get_api_template(&self, key: &str) -> Result<UrlTemplate> {
+        match self.api_urls().await.get(key) {
+            Some(a) => Ok(a.clone()),
+            None => Err(HisparcError::InvalidInput(format!("Key {} not found", key))),
+        }
+    }
+
+    // Discovers the endpoints again, e.g. in long-running processes. On
+    // failure the endpoints in use are kept.
+    pub async fn refresh_api_urls(&self) -> Result<()> {
+        let urls = self.get_api_urls_internal().await?;
+        self.api_urls.store_discovered(urls);
+        Ok(())
+    }
+
+    async fn execute<T: DeserializeOwned>(&self, request: ApiRequest<T>) -> Result<T> {
+        let url = request.url(&self.get_api_template(request.key).await?)?;
+        self.get_json_cached::<T>(&url, request.date()).await
+    }
+
+    pub async fn get_station_info<D: Into<Option<NaiveDate>>>(
+        &self,
+        station_number: u32,
+        date: D,
+    ) -> Result<StationInfo> {
+        self.execute(requests::station_info(station_number, date.into())?)
+            .await
+    }
+
+    pub async fn get_stations_with_data<D: Into<Option<NaiveDate>>>(
+        &self,
+        date: D,
+    ) -> Result<Vec<NameNumber>> {
+        self.execute(requests::stations_with_data(date.into())?)
+            .await
+    }
+
+    pub async fn get_has_singles<D: Into<Option<NaiveDate>>>(
+        &self,
+        station_number: u32,
+        date: D,
+    ) -> Result<bool> {
+        self.execute(requests::has_singles(station_number, date.into())?)
+            .await
+    }
+
+    pub async fn get_subclusters_in_cluster(&self, cluster_number: u32) -> Result<Vec<NameNumber>> {
+        self.execute(requests::subclusters_in_cluster(cluster_number))
+            .await
+    }
+
+    pub async fn get_configuration<D: Into<Option<NaiveDate>>>(
+        &self,
+        station_number: u32,
+        date: D,
+    ) -> Result<StationConfig> {
+        self.execute(requests::configuration(station_number, date.into())?)
+            .await
+    }
+
+    pub async fn get_clusters(&self) -> Result<Vec<NameNumber>> {
+        self.execute(requests::clusters()).await
+    }
+
+    // Counts over a year, month, day (a NaiveDate) or hour (a NaiveDateTime),
+    // or over all time
+    pub async fn get_number_of_events<P: Into<Period>>(
+        &self,
+        station_number: u32,
+        period: P,
+    ) -> Result<u32> {
+        self.execute(requests::number_of_events(station_number, period.into())?)
+            .await
+    }
+
+    pub async fn get_has_weather<D: Into<Option<NaiveDate>>>(
+        &self,
+        station_number: u32,
+        date: D,
+    ) -> Result<bool> {
+        self.execute(requests::has_weather(station_number, date.into())?)
+            .await
+    }
+
+    pub async fn get_has_data<D: Into<Option<NaiveDate>>>(
+        &self,
+        station_number: u32,
+        date: D,
+    ) -> Result<bool> {
+        self.execute(requests::has_data(station_number, date.into())?)
+            .await
+    }
+
+    pub async fn get_clusters_in_country(&self, country_number: u32) -> Result<Vec<NameNumber>> {
+        self.execute(requests::clusters_in_country(country_number))
+            .await
+    }
+
+    pub async fn get_stations_in_subcluster(
+        &self,
+        subcluster_number: u32,
+    ) -> Result<Vec<NameNumber>> {
+        self.execute(requests::stations_in_subcluster(subcluster_number))
+            .await
+    }
+
+    pub async fn get_event_trace(
+        &self,
+        station_number: u64,
+        ext_timestamp: u64,
+    ) -> Result<Vec<Vec<u32>>> {
+        self.execute(requests::event_trace(station_number, ext_timestamp))
+            .await
+    }
+
+    pub async fn get_stations(&self) -> Result<Vec<NameNumber>> {
+        self.execute(requests::stations()).await
+    }
+
+    pub async fn get_countries(&self) -> Result<Vec<NameNumber>> {
+        self.execute(requests::countries()).await
+    }
+
+    pub async fn get_stations_with_weather<D: Into<Option<NaiveDate>>>(
+        &self,
+        date: D,
+    ) -> Result<Vec<NameNumber>> {
+        self.execute(requests::stations_with_weather(date.into())?)
+            .await
+    }
+
+    pub async fn get_subclusters(&self) -> Result<Vec<NameNumber>> {
+        self.execute(requests::subclusters()).await
+    }
+
+    // Like HisparcClient::get_network, with up to NETWORK_WORKERS requests
+    // in flight at once
+    pub async fn get_network(&self) -> Result<Network> {
+        let countries = self.get_countries().await?;
+
+        let clusters: Vec<Vec<NameNumber>> = stream::iter(&countries)
+            .map(|x| async move {
+                self.get_clusters_in_country(x.number)
+                    .await
+                    .context(format!("getting clusters in country {}", x.number))
+            })
+            .buffered(NETWORK_WORKERS)
+            .try_collect()
+            .await?;
+
+        let subclusters: Vec<Vec<NameNumber>> = stream::iter(clusters.iter().flatten())
+            .map(|x| async move {
+                self.get_subclusters_in_cluster(x.number)
+                    .await
+                    .context(format!("getting subclusters in cluster {}", x.number))
+            })
+            .buffered(NETWORK_WORKERS)
+            .try_collect()
+            .await?;
+
+        let stations: Vec<Vec<NameNumber>> = stream::iter(subclusters.iter().flatten())
+            .map(|x| async move {
+                self.get_stations_in_subcluster(x.number)
+                    .await
+                    .context(format!("getting stations in subcluster {}", x.number))
+            })
+            .buffered(NETWORK_WORKERS)
+            .try_collect()
+            .await?;
+
+        Ok(assemble(countries, clusters, subclusters, stations))
+    }
+
+    // Like HisparcClient::get_station_catalogue, with up to
+    // CATALOGUE_WORKERS requests in flight at once
+    pub async fn get_station_catalogue<D: Into<Option<NaiveDate>>>(
+        &self,
+        date: D,
+    ) -> Result<StationCatalogue> {
+        let date = date.into();
+        let stations = self.get_stations().await?;
+
+        let results: Vec<Result<StationInfo>> = stream::iter(&stations)
+            .map(|x| async move {
+                self.get_station_info(x.number, date)
+                    .await
+                    .context(format!("getting info for station {}", x.number))
+            })
+            .buffered(CATALOGUE_WORKERS)
+            .collect()
+            .await;
+
+        collect_catalogue(results)
+    }
+}
+
+impl StationCatalogue {
+    // Like search, for the async client
+    pub async fn search_async(
+        &self,
+        client: &AsyncHisparcClient,
+        query: &StationQuery,
+    ) -> Result<Vec<&StationInfo>> {
+        let with_weather = match query.weather_on {
+            Some(date) => Some(numbers(client.get_stations_with_weather(date).await)?),
+            None => None,
+        };
+        let with_data = match query.data_on {
+            Some(date) => Some(numbers(client.get_stations_with_data(date).await)?),
+            None => None,
+        };
+
+        Ok(self.search_in(query, with_weather, with_data))
+    }
+}
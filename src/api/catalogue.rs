@@ -0,0 +1,291 @@
+use crate::api::structs::{NameNumber, StationInfo};
+use crate::client::HisparcClient;
+use crate::concurrent::run_concurrently;
+use crate::error::{Context, HisparcError, Result};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::ops::{Bound, RangeBounds};
+
+// Requests in flight at once while loading the catalogue
+pub(crate) const CATALOGUE_WORKERS: usize = 8;
+
+// Mean radius of the earth in km
+const EARTH_RADIUS: f64 = 6371.0088;
+
+// The StationInfo of every station, for searching without a request per
+// station. Serializes to JSON, so it can be kept for offline use.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StationCatalogue {
+    pub stations: Vec<StationInfo>,
+}
+
+// Conditions a station must meet to be found. Everything left unset matches.
+// Names are compared without regard to case.
+#[derive(Debug, Clone, Default)]
+pub struct StationQuery {
+    active: Option<bool>,
+    country: Option<String>,
+    cluster: Option<String>,
+    subcluster: Option<String>,
+    name: Option<String>,
+    scintillators: Option<(Bound<usize>, Bound<usize>)>,
+    area: Option<Area>,
+    pub(crate) weather_on: Option<NaiveDate>,
+    pub(crate) data_on: Option<NaiveDate>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Area {
+    BoundingBox {
+        south: f64,
+        west: f64,
+        north: f64,
+        east: f64,
+    },
+    Radius {
+        latitude: f64,
+        longitude: f64,
+        km: f64,
+    },
+}
+
+impl StationQuery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn active(mut self, active: bool) -> Self {
+        self.active = Some(active);
+        self
+    }
+
+    pub fn country<S: Into<String>>(mut self, country: S) -> Self {
+        self.country = Some(country.into());
+        self
+    }
+
+    pub fn cluster<S: Into<String>>(mut self, cluster: S) -> Self {
+        self.cluster = Some(cluster.into());
+        self
+    }
+
+    pub fn subcluster<S: Into<String>>(mut self, subcluster: S) -> Self {
+        self.subcluster = Some(subcluster.into());
+        self
+    }
+
+    // Matches any part of the station name
+    pub fn name_contains<S: Into<String>>(mut self, name: S) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    // For example 4.. or 2..=2
+    pub fn scintillators<R: RangeBounds<usize>>(mut self, range: R) -> Self {
+        self.scintillators = Some((range.start_bound().cloned(), range.end_bound().cloned()));
+        self
+    }
+
+    // Stations without a position never match an area
+    pub fn within_bounding_box(mut self, south: f64, west: f64, north: f64, east: f64) -> Self {
+        self.area = Some(Area::BoundingBox {
+            south,
+            west,
+            north,
+            east,
+        });
+        self
+    }
+
+    pub fn within_km(mut self, latitude: f64, longitude: f64, km: f64) -> Self {
+        self.area = Some(Area::Radius {
+            latitude,
+            longitude,
+            km,
+        });
+        self
+    }
+
+    // Needs one request per date when searching
+    pub fn has_weather_on(mut self, date: NaiveDate) -> Self {
+        self.weather_on = Some(date);
+        self
+    }
+
+    pub fn has_data_on(mut self, date: NaiveDate) -> Self {
+        self.data_on = Some(date);
+        self
+    }
+
+    // Checks everything but the weather and data dates, which need the API
+    pub fn matches(&self, station: &StationInfo) -> bool {
+        let same_name = |wanted: &Option<String>, name: &str| {
+            wanted.as_ref().is_none_or(|x| x.eq_ignore_ascii_case(name))
+        };
+
+        self.active.is_none_or(|x| x == station.active)
+            && same_name(&self.country, &station.country)
+            && same_name(&self.cluster, &station.cluster)
+            && same_name(&self.subcluster, &station.subcluster)
+            && self
+                .name
+                .as_ref()
+                .is_none_or(|x| station.name.to_lowercase().contains(&x.to_lowercase()))
+            && self
+                .scintillators
+                .is_none_or(|x| x.contains(&station.scintillators.len()))
+            && self.area.is_none_or(|x| x.contains(station))
+    }
+}
+
+impl Area {
+    fn contains(&self, station: &StationInfo) -> bool {
+        let (latitude, longitude) = match station.position() {
+            Some(position) => position,
+            None => return false,
+        };
+
+        match *self {
+            Area::BoundingBox {
+                south,
+                west,
+                north,
+                east,
+            } => (south..=north).contains(&latitude) && (west..=east).contains(&longitude),
+            Area::Radius {
+                latitude: centre_latitude,
+                longitude: centre_longitude,
+                km,
+            } => distance_km(latitude, longitude, centre_latitude, centre_longitude) <= km,
+        }
+    }
+}
+
+impl StationInfo {
+    // Latitude and longitude in degrees, if the station has them
+    pub fn position(&self) -> Option<(f64, f64)> {
+        Some((self.latitude? as f64, self.longitude? as f64))
+    }
+
+    // Great-circle distance to a point, if the station has a position
+    pub fn distance_km(&self, latitude: f64, longitude: f64) -> Option<f64> {
+        self.position()
+            .map(|(x, y)| distance_km(x, y, latitude, longitude))
+    }
+}
+
+// Haversine distance between two points given in degrees
+pub fn distance_km(latitude_1: f64, longitude_1: f64, latitude_2: f64, longitude_2: f64) -> f64 {
+    let (phi_1, phi_2) = (latitude_1.to_radians(), latitude_2.to_radians());
+    let delta_phi = phi_2 - phi_1;
+    let delta_lambda = (longitude_2 - longitude_1).to_radians();
+
+    let a = (delta_phi / 2.0).sin().powi(2)
+        + phi_1.cos() * phi_2.cos() * (delta_lambda / 2.0).sin().powi(2);
+
+    2.0 * EARTH_RADIUS * a.sqrt().asin()
+}
+
+impl StationCatalogue {
+    pub fn station(&self, number: u32) -> Option<&StationInfo> {
+        self.stations.iter().find(|x| x.number == number)
+    }
+
+    // Only the conditions that need no requests, see StationQuery::matches
+    pub fn filter<'a>(&'a self, query: &'a StationQuery) -> impl Iterator<Item = &'a StationInfo> {
+        self.stations.iter().filter(move |x| query.matches(x))
+    }
+
+    // Every station matching the query. The weather and data conditions
+    // take one request each, whatever the number of stations.
+    pub fn search(
+        &self,
+        client: &HisparcClient,
+        query: &StationQuery,
+    ) -> Result<Vec<&StationInfo>> {
+        let with_weather = match query.weather_on {
+            Some(date) => Some(numbers(client.get_stations_with_weather(date))?),
+            None => None,
+        };
+        let with_data = match query.data_on {
+            Some(date) => Some(numbers(client.get_stations_with_data(date))?),
+            None => None,
+        };
+
+        Ok(self.search_in(query, with_weather, with_data))
+    }
+
+    // The search itself, given the stations with weather and data on the
+    // query's dates, so both clients can share it
+    pub(crate) fn search_in(
+        &self,
+        query: &StationQuery,
+        with_weather: Option<HashSet<u32>>,
+        with_data: Option<HashSet<u32>>,
+    ) -> Vec<&StationInfo> {
+        self.stations
+            .iter()
+            .filter(|x| query.matches(x))
+            .filter(|x| with_weather.as_ref().is_none_or(|s| s.contains(&x.number)))
+            .filter(|x| with_data.as_ref().is_none_or(|s| s.contains(&x.number)))
+            .collect()
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(self).map_err(HisparcError::parse)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self> {
+        serde_json::from_str(json)
+            .map_err(HisparcError::parse)
+            .context("parsing station catalogue")
+    }
+}
+
+// A day without any station is answered with a 404
+pub(crate) fn numbers(stations: Result<Vec<NameNumber>>) -> Result<HashSet<u32>> {
+    match stations {
+        Ok(stations) => Ok(stations.into_iter().map(|x| x.number).collect()),
+        Err(error) if error.is_not_found() => Ok(HashSet::new()),
+        Err(error) => Err(error),
+    }
+}
+
+impl HisparcClient {
+    // Loads the StationInfo of every station concurrently, as it was on the
+    // given date or as it is now. Stations without information for that date
+    // are left out.
+    pub fn get_station_catalogue<D: Into<Option<NaiveDate>>>(
+        &self,
+        date: D,
+    ) -> Result<StationCatalogue> {
+        let date = date.into();
+        let stations = self.get_stations()?;
+
+        let results = run_concurrently(&stations, CATALOGUE_WORKERS, |x| {
+            self.get_station_info(x.number, date)
+                .context(format!("getting info for station {}", x.number))
+        });
+
+        collect_catalogue(results)
+    }
+}
+
+// Stations without information for the date are left out
+pub(crate) fn collect_catalogue(results: Vec<Result<StationInfo>>) -> Result<StationCatalogue> {
+    let mut infos = Vec::with_capacity(results.len());
+    for result in results {
+        match result {
+            Ok(info) => infos.push(info),
+            Err(error) if error.is_not_found() => {}
+            Err(error) => return Err(error),
+        }
+    }
+
+    Ok(StationCatalogue { stations: infos })
+}
+
+pub fn get_station_catalogue<D: Into<Option<NaiveDate>>>(date: D) -> Result<StationCatalogue> {
+    HisparcClient::shared().get_station_catalogue(date)
+}
@@ -0,0 +1,334 @@
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::de::{Deserializer, Error as _};
+use serde::ser::Serializer;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+// How the API writes timestamps, which are UTC
+const TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+// The settings of a station at some moment. The API sends these as one flat
+// object with a prefix per unit and channel, such as mas_ch1_voltage, which
+// is also what this serializes to.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StationConfig {
+    pub station_number: u32,
+    pub timestamp: DateTime<Utc>,
+    pub electronics: Electronics,
+    pub trigger: TriggerConfig,
+    pub gps: GpsPosition,
+    pub delays: Delays,
+    pub reduce_data: bool,
+    pub use_filter: bool,
+    pub use_filter_threshold: bool,
+    pub start_mode: bool,
+    pub summary: u32,
+    pub spare_bytes: u32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Electronics {
+    pub master: Unit,
+    pub slave: Unit,
+}
+
+// One box of electronics with its two channels
+#[derive(Debug, Clone, PartialEq)]
+pub struct Unit {
+    pub version: String,
+    pub reset: bool,
+    pub common_offset: f32,
+    pub internal_voltage: f32,
+    pub max_voltage: f32,
+    pub comparator_thresholds: Thresholds,
+    pub channels: [Channel; 2],
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Channel {
+    pub voltage: f32,
+    pub current: f32,
+    pub integration_time: f32,
+    pub thresholds: Thresholds,
+    pub gains: Calibration,
+    pub offsets: Calibration,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Thresholds {
+    pub low: f32,
+    pub high: f32,
+}
+
+// Used for both the gains and the offsets of a channel
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Calibration {
+    pub positive: f32,
+    pub negative: f32,
+    pub adc: f32,
+    pub comparator: f32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TriggerConfig {
+    pub coincidence_time: f32,
+    pub pre_coincidence_time: f32,
+    pub post_coincidence_time: f32,
+    pub low_signals: u32,
+    pub high_signals: u32,
+    pub and_or: bool,
+    pub external: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GpsPosition {
+    pub latitude: f64,
+    pub longitude: f64,
+    pub altitude: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Delays {
+    pub screen: f32,
+    pub check: f32,
+    pub error: f32,
+}
+
+impl<'de> Deserialize<'de> for StationConfig {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let fields = Fields(Map::deserialize(deserializer)?);
+        StationConfig::read(&fields).map_err(D::Error::custom)
+    }
+}
+
+impl Serialize for StationConfig {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut fields = Map::new();
+        self.write(&mut fields);
+        fields.serialize(serializer)
+    }
+}
+
+type ReadResult<T> = std::result::Result<T, String>;
+
+impl StationConfig {
+    fn read(fields: &Fields) -> ReadResult<Self> {
+        Ok(Self {
+            station_number: fields.integer("detnum")?,
+            timestamp: fields.timestamp("timestamp")?,
+            electronics: Electronics {
+                master: Unit::read(fields, "mas")?,
+                slave: Unit::read(fields, "slv")?,
+            },
+            trigger: TriggerConfig {
+                coincidence_time: fields.float("coinctime")?,
+                pre_coincidence_time: fields.float("precoinctime")?,
+                post_coincidence_time: fields.float("postcoinctime")?,
+                low_signals: fields.integer("trig_low_signals")?,
+                high_signals: fields.integer("trig_high_signals")?,
+                and_or: fields.flag("trig_and_or")?,
+                external: fields.integer("trig_external")?,
+            },
+            gps: GpsPosition {
+                latitude: fields.double("gps_latitude")?,
+                longitude: fields.double("gps_longitude")?,
+                altitude: fields.double("gps_altitude")?,
+            },
+            delays: Delays {
+                screen: fields.float("delay_screen")?,
+                check: fields.float("delay_check")?,
+                error: fields.float("delay_error")?,
+            },
+            reduce_data: fields.flag("reduce_data")?,
+            use_filter: fields.flag("use_filter")?,
+            use_filter_threshold: fields.flag("use_filter_threshold")?,
+            start_mode: fields.flag("startmode")?,
+            summary: fields.integer("summary")?,
+            spare_bytes: fields.integer("spare_bytes")?,
+        })
+    }
+
+    fn write(&self, fields: &mut Map<String, Value>) {
+        let mut put = |name: &str, value: Value| {
+            fields.insert(name.to_string(), value);
+        };
+
+        put("detnum", self.station_number.into());
+        put(
+            "timestamp",
+            self.timestamp.format(TIMESTAMP_FORMAT).to_string().into(),
+        );
+        put("coinctime", self.trigger.coincidence_time.into());
+        put("precoinctime", self.trigger.pre_coincidence_time.into());
+        put("postcoinctime", self.trigger.post_coincidence_time.into());
+        put("trig_low_signals", self.trigger.low_signals.into());
+        put("trig_high_signals", self.trigger.high_signals.into());
+        put("trig_and_or", self.trigger.and_or.into());
+        put("trig_external", self.trigger.external.into());
+        put("gps_latitude", self.gps.latitude.into());
+        put("gps_longitude", self.gps.longitude.into());
+        put("gps_altitude", self.gps.altitude.into());
+        put("delay_screen", self.delays.screen.into());
+        put("delay_check", self.delays.check.into());
+        put("delay_error", self.delays.error.into());
+        put("reduce_data", self.reduce_data.into());
+        put("use_filter", self.use_filter.into());
+        put("use_filter_threshold", self.use_filter_threshold.into());
+        put("startmode", self.start_mode.into());
+        put("summary", self.summary.into());
+        put("spare_bytes", self.spare_bytes.into());
+
+        self.electronics.master.write(fields, "mas");
+        self.electronics.slave.write(fields, "slv");
+    }
+}
+
+impl Unit {
+    fn read(fields: &Fields, prefix: &str) -> ReadResult<Self> {
+        let name = |field: &str| format!("{}_{}", prefix, field);
+
+        Ok(Self {
+            version: fields.text(&name("version"))?,
+            reset: fields.flag(&name("reset"))?,
+            common_offset: fields.float(&name("common_offset"))?,
+            internal_voltage: fields.float(&name("internal_voltage"))?,
+            max_voltage: fields.float(&name("max_voltage"))?,
+            comparator_thresholds: Thresholds {
+                low: fields.float(&name("comp_thres_low"))?,
+                high: fields.float(&name("comp_thres_high"))?,
+            },
+            channels: [
+                Channel::read(fields, &name("ch1"))?,
+                Channel::read(fields, &name("ch2"))?,
+            ],
+        })
+    }
+
+    fn write(&self, fields: &mut Map<String, Value>, prefix: &str) {
+        let mut put = |field: &str, value: Value| {
+            fields.insert(format!("{}_{}", prefix, field), value);
+        };
+
+        put("version", self.version.clone().into());
+        put("reset", self.reset.into());
+        put("common_offset", self.common_offset.into());
+        put("internal_voltage", self.internal_voltage.into());
+        put("max_voltage", self.max_voltage.into());
+        put("comp_thres_low", self.comparator_thresholds.low.into());
+        put("comp_thres_high", self.comparator_thresholds.high.into());
+
+        self.channels[0].write(fields, &format!("{}_ch1", prefix));
+        self.channels[1].write(fields, &format!("{}_ch2", prefix));
+    }
+}
+
+impl Channel {
+    fn read(fields: &Fields, prefix: &str) -> ReadResult<Self> {
+        let name = |field: &str| format!("{}_{}", prefix, field);
+
+        Ok(Self {
+            voltage: fields.float(&name("voltage"))?,
+            current: fields.float(&name("current"))?,
+            integration_time: fields.float(&name("inttime"))?,
+            thresholds: Thresholds {
+                low: fields.float(&name("thres_low"))?,
+                high: fields.float(&name("thres_high"))?,
+            },
+            gains: Calibration {
+                positive: fields.float(&name("gain_pos"))?,
+                negative: fields.float(&name("gain_neg"))?,
+                adc: fields.float(&name("adc_gain"))?,
+                comparator: fields.float(&name("comp_gain"))?,
+            },
+            offsets: Calibration {
+                positive: fields.float(&name("offset_pos"))?,
+                negative: fields.float(&name("offset_neg"))?,
+                adc: fields.float(&name("adc_offset"))?,
+                comparator: fields.float(&name("comp_offset"))?,
+            },
+        })
+    }
+
+    fn write(&self, fields: &mut Map<String, Value>, prefix: &str) {
+        let mut put = |field: &str, value: Value| {
+            fields.insert(format!("{}_{}", prefix, field), value);
+        };
+
+        put("voltage", self.voltage.into());
+        put("current", self.current.into());
+        put("inttime", self.integration_time.into());
+        put("thres_low", self.thresholds.low.into());
+        put("thres_high", self.thresholds.high.into());
+        put("gain_pos", self.gains.positive.into());
+        put("gain_neg", self.gains.negative.into());
+        put("adc_gain", self.gains.adc.into());
+        put("comp_gain", self.gains.comparator.into());
+        put("offset_pos", self.offsets.positive.into());
+        put("offset_neg", self.offsets.negative.into());
+        put("adc_offset", self.offsets.adc.into());
+        put("comp_offset", self.offsets.comparator.into());
+    }
+}
+
+// The flat object as sent by the API. Numbers that are really integers or
+// flags may come as floats, such as 501.0 or 1.0, so those are accepted too.
+struct Fields(Map<String, Value>);
+
+impl Fields {
+    fn get(&self, name: &str) -> ReadResult<&Value> {
+        self.0
+            .get(name)
+            .ok_or_else(|| format!("missing field {}", name))
+    }
+
+    fn double(&self, name: &str) -> ReadResult<f64> {
+        self.get(name)?
+            .as_f64()
+            .ok_or_else(|| format!("{} is not a number", name))
+    }
+
+    fn float(&self, name: &str) -> ReadResult<f32> {
+        self.double(name).map(|x| x as f32)
+    }
+
+    fn integer(&self, name: &str) -> ReadResult<u32> {
+        let value = self.get(name)?;
+        value
+            .as_u64()
+            .or_else(|| {
+                value
+                    .as_f64()
+                    .filter(|x| x.fract() == 0.0 && *x >= 0.0)
+                    .map(|x| x as u64)
+            })
+            .and_then(|x| u32::try_from(x).ok())
+            .ok_or_else(|| format!("{} is not a whole number: {}", name, value))
+    }
+
+    fn flag(&self, name: &str) -> ReadResult<bool> {
+        match self.get(name)? {
+            Value::Bool(flag) => Ok(*flag),
+            value => match value.as_f64() {
+                Some(x) if x == 0.0 || x == 1.0 => Ok(x == 1.0),
+                _ => Err(format!("{} is not a flag: {}", name, value)),
+            },
+        }
+    }
+
+    fn text(&self, name: &str) -> ReadResult<String> {
+        match self.get(name)? {
+            Value::String(text) => Ok(text.clone()),
+            value => Err(format!("{} is not text: {}", name, value)),
+        }
+    }
+
+    // Either the API's own format or RFC 3339
+    fn timestamp(&self, name: &str) -> ReadResult<DateTime<Utc>> {
+        let text = self.text(name)?;
+
+        NaiveDateTime::parse_from_str(&text, TIMESTAMP_FORMAT)
+            .map(|x| x.and_utc())
+            .or_else(|_| DateTime::parse_from_rfc3339(&text).map(|x| x.with_timezone(&Utc)))
+            .map_err(|_| format!("{} is not a timestamp: {}", name, text))
+    }
+}
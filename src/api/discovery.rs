@@ -0,0 +1,145 @@
+use crate::api::template::UrlTemplate;
+use crate::error::Result;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+// How long the built-in endpoint table is used after a failed discovery
+// before discovery is attempted again, unless set on the client.
+pub(crate) const DISCOVERY_RETRY_INTERVAL: Duration = Duration::from_secs(60);
+
+// The endpoint templates served by the API root, as known when this was
+// written. Used when the API root cannot be reached.
+const KNOWN_API_URLS: &[(&str, &str)] = &[
+    ("clusters", "clusters/"),
+    ("clusters_in_country", "countries/{country_number}/"),
+    (
+        "configuration",
+        "station/{station_number}/config/{year}/{month}/{day}/",
+    ),
+    ("countries", "countries/"),
+    (
+        "event_trace",
+        "station/{station_number}/trace/{ext_timestamp}/",
+    ),
+    (
+        "has_data",
+        "station/{station_number}/data/{year}/{month}/{day}/",
+    ),
+    (
+        "has_singles",
+        "station/{station_number}/singles/{year}/{month}/{day}/",
+    ),
+    (
+        "has_weather",
+        "station/{station_number}/weather/{year}/{month}/{day}/",
+    ),
+    (
+        "number_of_events",
+        "station/{station_number}/num_events/{year}/{month}/{day}/{hour}/",
+    ),
+    (
+        "station_info",
+        "station/{station_number}/{year}/{month}/{day}/",
+    ),
+    ("stations", "stations/"),
+    ("stations_in_subcluster", "subclusters/{subcluster_number}/"),
+    ("stations_with_data", "stations/data/{year}/{month}/{day}/"),
+    (
+        "stations_with_weather",
+        "stations/weather/{year}/{month}/{day}/",
+    ),
+    ("subclusters", "subclusters/"),
+    ("subclusters_in_cluster", "clusters/{cluster_number}/"),
+];
+
+// Discovered endpoints are kept for the lifetime of the client, unless
+// refreshed. A failed discovery is not remembered: the built-in table is used
+// for a while, after which discovery is attempted again.
+pub(crate) struct ApiUrlCache {
+    state: Mutex<ApiUrlState>,
+    retry_interval: Duration,
+}
+
+#[derive(Default)]
+struct ApiUrlState {
+    urls: Option<Arc<HashMap<String, UrlTemplate>>>,
+    retry_discovery_at: Option<Instant>,
+}
+
+impl ApiUrlCache {
+    pub(crate) fn new(retry_interval: Duration) -> Self {
+        Self {
+            state: Mutex::default(),
+            retry_interval,
+        }
+    }
+
+    pub(crate) fn current(&self) -> Option<Arc<HashMap<String, UrlTemplate>>> {
+        let state = self.state.lock().unwrap();
+
+        match state.retry_discovery_at {
+            Some(retry_at) if Instant::now() >= retry_at => None,
+            _ => state.urls.clone(),
+        }
+    }
+
+    pub(crate) fn store_discovered(
+        &self,
+        urls: HashMap<String, UrlTemplate>,
+    ) -> Arc<HashMap<String, UrlTemplate>> {
+        let urls = Arc::new(urls);
+        let mut state = self.state.lock().unwrap();
+
+        state.urls = Some(urls.clone());
+        state.retry_discovery_at = None;
+
+        urls
+    }
+
+    // Only called after a failed discovery, which does not happen while
+    // discovered endpoints are stored.
+    pub(crate) fn store_fallback(&self, api_url: &str) -> Arc<HashMap<String, UrlTemplate>> {
+        let urls = Arc::new(known_api_urls(api_url));
+        let mut state = self.state.lock().unwrap();
+
+        state.urls = Some(urls.clone());
+        state.retry_discovery_at = Some(Instant::now() + self.retry_interval);
+
+        urls
+    }
+}
+
+pub(crate) fn known_api_urls(api_url: &str) -> HashMap<String, UrlTemplate> {
+    resolve_api_urls(
+        api_url,
+        KNOWN_API_URLS
+            .iter()
+            .map(|(key, template)| (key.to_string(), template.to_string()))
+            .collect(),
+    )
+    .expect("the known API templates are valid")
+}
+
+// The API root lists its endpoints relative to itself, next to a `base_url`
+// entry that is not an endpoint. A template that cannot be parsed fails the
+// whole discovery, so that the known endpoints are used instead.
+pub(crate) fn resolve_api_urls(
+    api_url: &str,
+    api_urls: HashMap<String, String>,
+) -> Result<HashMap<String, UrlTemplate>> {
+    let mut new_api_urls: HashMap<String, UrlTemplate> = HashMap::new();
+
+    // Braces in the base URL are literal
+    let api_url = api_url.replace('{', "{{").replace('}', "}}");
+
+    for (key, temp) in api_urls {
+        if key == "base_url" {
+            continue;
+        }
+        let modded_temp = UrlTemplate::parse(&(api_url.clone() + &temp))?;
+        new_api_urls.insert(key, modded_temp);
+    }
+
+    Ok(new_api_urls)
+}
@@ -1,271 +1,239 @@
-use anyhow::{anyhow, Result};
-use std::{collections::HashMap, fmt::Display};
-use once_cell::sync::OnceCell;
+use crate::api::config::StationConfig;
+use crate::api::discovery::resolve_api_urls;
+use crate::api::requests::{self, ApiRequest};
 use crate::api::structs::*;
+use crate::api::template::UrlTemplate;
+use crate::client::HisparcClient;
+use crate::error::{HisparcError, Result};
+use chrono::NaiveDate;
+use serde::de::DeserializeOwned;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+impl HisparcClient {
+    fn get_api_urls_internal(&self) -> Result<HashMap<String, UrlTemplate>> {
+        let api_urls = self.get_json::<HashMap<String, String>>(&self.api_url)?;
+
+        resolve_api_urls(&self.api_url, api_urls)
+    }
 
-const BASE_URL: &str = "https://data.hisparc.nl/api/";
+    fn api_urls(&self) -> Arc<HashMap<String, UrlTemplate>> {
+        if let Some(urls) = self.api_urls.current() {
+            return urls;
+        }
 
-fn get_api_urls_internal() -> Result<HashMap<String, String>> {
-    let api_urls = reqwest::blocking::get(BASE_URL)?.json::<HashMap<String, String>>()?;
+        match self.get_api_urls_internal() {
+            Ok(urls) => self.api_urls.store_discovered(urls),
+            Err(_) => self.api_urls.store_fallback(&self.api_url),
+        }
+    }
 
-    let mut new_api_urls: HashMap<String, String> = HashMap::new();
+    // Falls back to the known endpoint templates when the API root cannot be
+    // reached, and tries discovery again after the client's discovery retry
+    // interval.
+    pub fn get_api_url(&self, key: &str) -> Result<String> {
+        Ok(self.get_api_template(key)?.as_str().to_string())
+    }
 
-    for key in api_urls.keys() {
-        if key == "base_url" {
-            continue;
+    pub fn get_api_template(&self, key: &str) -> Result<UrlTemplate> {
+        match self.api_urls().get(key) {
+            Some(a) => Ok(a.clone()),
+            None => Err(HisparcError::InvalidInput(format!("Key {} not found", key))),
         }
-        let temp = api_urls.get(key).unwrap();
-        let modded_temp = BASE_URL.to_string() + temp;
-        new_api_urls.insert(key.to_string(), modded_temp);
     }
 
-    Ok(new_api_urls)
-}
+    // Discovers the endpoints again, e.g. in long-running processes. On
+    // failure the endpoints in use are kept.
+    pub fn refresh_api_urls(&self) -> Result<()> {
+        let urls = self.get_api_urls_internal()?;
+        self.api_urls.store_discovered(urls);
+        Ok(())
+    }
+
+    fn execute<T: DeserializeOwned>(&self, request: ApiRequest<T>) -> Result<T> {
+        let url = request.url(&self.get_api_template(request.key)?)?;
+        self.get_json_cached::<T>(&url, request.date())
+    }
+
+    pub fn get_station_info<D: Into<Option<NaiveDate>>>(
+        &self,
+        station_number: u32,
+        date: D,
+    ) -> Result<StationInfo> {
+        self.execute(requests::station_info(station_number, date.into())?)
+    }
+
+    pub fn get_stations_with_data<D: Into<Option<NaiveDate>>>(
+        &self,
+        date: D,
+    ) -> Result<Vec<NameNumber>> {
+        self.execute(requests::stations_with_data(date.into())?)
+    }
 
-static API_URLS: OnceCell<Result<HashMap<String, String>>> = OnceCell::new();
-
-pub fn get_api_url(key: &str) -> Result<&'static str> {
-    let data = API_URLS.get_or_init(|| {
-        get_api_urls_internal()
-    });
-
-    match data.as_ref() {
-        Ok(map) => {
-            match map.get(key) {
-                Some(a) => Ok(a),
-                None => Err(anyhow!("Key {} not found", key))
-            }
-        },
-        Err(e) => return Err(anyhow!(e.to_string()))
+    pub fn get_has_singles<D: Into<Option<NaiveDate>>>(
+        &self,
+        station_number: u32,
+        date: D,
+    ) -> Result<bool> {
+        self.execute(requests::has_singles(station_number, date.into())?)
+    }
+
+    pub fn get_subclusters_in_cluster(&self, cluster_number: u32) -> Result<Vec<NameNumber>> {
+        self.execute(requests::subclusters_in_cluster(cluster_number))
+    }
+
+    pub fn get_configuration<D: Into<Option<NaiveDate>>>(
+        &self,
+        station_number: u32,
+        date: D,
+    ) -> Result<StationConfig> {
+        self.execute(requests::configuration(station_number, date.into())?)
+    }
+
+    pub fn get_clusters(&self) -> Result<Vec<NameNumber>> {
+        self.execute(requests::clusters())
+    }
+
+    // Counts over a year, month, day (a NaiveDate) or hour (a NaiveDateTime),
+    // or over all time
+    pub fn get_number_of_events<P: Into<Period>>(
+        &self,
+        station_number: u32,
+        period: P,
+    ) -> Result<u32> {
+        self.execute(requests::number_of_events(station_number, period.into())?)
+    }
+
+    pub fn get_has_weather<D: Into<Option<NaiveDate>>>(
+        &self,
+        station_number: u32,
+        date: D,
+    ) -> Result<bool> {
+        self.execute(requests::has_weather(station_number, date.into())?)
+    }
+
+    pub fn get_has_data<D: Into<Option<NaiveDate>>>(
+        &self,
+        station_number: u32,
+        date: D,
+    ) -> Result<bool> {
+        self.execute(requests::has_data(station_number, date.into())?)
+    }
+
+    pub fn get_clusters_in_country(&self, country_number: u32) -> Result<Vec<NameNumber>> {
+        self.execute(requests::clusters_in_country(country_number))
+    }
+
+    pub fn get_stations_in_subcluster(&self, subcluster_number: u32) -> Result<Vec<NameNumber>> {
+        self.execute(requests::stations_in_subcluster(subcluster_number))
+    }
+
+    pub fn get_event_trace(
+        &self,
+        station_number: u64,
+        ext_timestamp: u64,
+    ) -> Result<Vec<Vec<u32>>> {
+        self.execute(requests::event_trace(station_number, ext_timestamp))
+    }
+
+    pub fn get_stations(&self) -> Result<Vec<NameNumber>> {
+        self.execute(requests::stations())
+    }
+
+    pub fn get_countries(&self) -> Result<Vec<NameNumber>> {
+        self.execute(requests::countries())
+    }
+
+    pub fn get_stations_with_weather<D: Into<Option<NaiveDate>>>(
+        &self,
+        date: D,
+    ) -> Result<Vec<NameNumber>> {
+        self.execute(requests::stations_with_weather(date.into())?)
+    }
+
+    pub fn get_subclusters(&self) -> Result<Vec<NameNumber>> {
+        self.execute(requests::subclusters())
     }
 }
 
-pub fn get_station_info(
+pub fn get_api_url(key: &str) -> Result<String> {
+    HisparcClient::shared().get_api_url(key)
+}
+
+pub fn get_api_template(key: &str) -> Result<UrlTemplate> {
+    HisparcClient::shared().get_api_template(key)
+}
+
+pub fn refresh_api_urls() -> Result<()> {
+    HisparcClient::shared().refresh_api_urls()
+}
+
+pub fn get_station_info<D: Into<Option<NaiveDate>>>(
     station_number: u32,
-    year: u32,
-    month: u32,
-    day: u32,
+    date: D,
 ) -> Result<StationInfo> {
-    let mut substitions = HashMap::new();
-    substitions.insert("station_number".to_string(), station_number);
-    substitions.insert("year".to_string(), year);
-    substitions.insert("month".to_string(), month);
-    substitions.insert("day".to_string(), day);
-
-    let url = substitute_variables_with_numbers(
-        get_api_url("station_info")?,
-        substitions,
-    )?;
-    let stations = reqwest::blocking::get(url)?.json::<StationInfo>()?;
-    Ok(stations)
+    HisparcClient::shared().get_station_info(station_number, date)
 }
 
-pub fn get_stations_with_data(year: u32, month: u32, day: u32) -> Result<Vec<NameNumber>> {
-    let mut substitions = HashMap::new();
-    substitions.insert("year".to_string(), year);
-    substitions.insert("month".to_string(), month);
-    substitions.insert("day".to_string(), day);
-
-    let url = substitute_variables_with_numbers(
-        get_api_url("stations_with_data")?,
-        substitions,
-    )?;
-    let stations = reqwest::blocking::get(url)?.json::<Vec<NameNumber>>()?;
-    Ok(stations)
+pub fn get_stations_with_data<D: Into<Option<NaiveDate>>>(date: D) -> Result<Vec<NameNumber>> {
+    HisparcClient::shared().get_stations_with_data(date)
 }
 
-pub fn get_has_singles(station_number: u32, year: u32, month: u32, day: u32) -> Result<bool> {
-    let mut substitions = HashMap::new();
-    substitions.insert("station_number".to_string(), station_number);
-    substitions.insert("year".to_string(), year);
-    substitions.insert("month".to_string(), month);
-    substitions.insert("day".to_string(), day);
-
-    let url = substitute_variables_with_numbers(
-        get_api_url("has_singles")?,
-        substitions,
-    )?;
-    let stations = reqwest::blocking::get(url)?.json::<bool>()?;
-    Ok(stations)
+pub fn get_has_singles<D: Into<Option<NaiveDate>>>(station_number: u32, date: D) -> Result<bool> {
+    HisparcClient::shared().get_has_singles(station_number, date)
 }
 
 pub fn get_subclusters_in_cluster(cluster_number: u32) -> Result<Vec<NameNumber>> {
-    let mut substitions = HashMap::new();
-    substitions.insert("cluster_number".to_string(), cluster_number);
-
-    let url = substitute_variables_with_numbers(
-        get_api_url("subclusters_in_cluster")?,
-        substitions,
-    )?;
-    let stations = reqwest::blocking::get(url)?.json::<Vec<NameNumber>>()?;
-    Ok(stations)
+    HisparcClient::shared().get_subclusters_in_cluster(cluster_number)
 }
 
-pub fn get_configuration(
+pub fn get_configuration<D: Into<Option<NaiveDate>>>(
     station_number: u32,
-    year: u32,
-    month: u32,
-    day: u32,
+    date: D,
 ) -> Result<StationConfig> {
-    let mut substitions = HashMap::new();
-    substitions.insert("station_number".to_string(), station_number);
-    substitions.insert("year".to_string(), year);
-    substitions.insert("month".to_string(), month);
-    substitions.insert("day".to_string(), day);
-
-    let url = substitute_variables_with_numbers(
-        get_api_url("configuration")?,
-        substitions,
-    )?;
-    let stations = reqwest::blocking::get(url)?.json::<StationConfig>()?;
-    Ok(stations)
+    HisparcClient::shared().get_configuration(station_number, date)
 }
 
 pub fn get_clusters() -> Result<Vec<NameNumber>> {
-    let stations = reqwest::blocking::get(get_api_url("clusters")?)?
-        .json::<Vec<NameNumber>>()?;
-    Ok(stations)
+    HisparcClient::shared().get_clusters()
 }
 
-pub fn get_number_of_events(
-    station_number: u32,
-    year: u32,
-    month: u32,
-    day: u32,
-    hour: u32,
-) -> Result<u32> {
-    let mut substitions = HashMap::new();
-    substitions.insert("station_number".to_string(), station_number);
-    substitions.insert("year".to_string(), year);
-    substitions.insert("month".to_string(), month);
-    substitions.insert("day".to_string(), day);
-    substitions.insert("hour".to_string(), hour);
-
-    let url = substitute_variables_with_numbers(
-        get_api_url("number_of_events")?,
-        substitions,
-    )?;
-    let stations = reqwest::blocking::get(url)?.json::<u32>()?;
-    Ok(stations)
+pub fn get_number_of_events<P: Into<Period>>(station_number: u32, period: P) -> Result<u32> {
+    HisparcClient::shared().get_number_of_events(station_number, period)
 }
 
-pub fn get_has_weather(station_number: u32, year: u32, month: u32, day: u32) -> Result<bool> {
-    let mut substitions = HashMap::new();
-    substitions.insert("station_number".to_string(), station_number);
-    substitions.insert("year".to_string(), year);
-    substitions.insert("month".to_string(), month);
-    substitions.insert("day".to_string(), day);
-
-    let url = substitute_variables_with_numbers(
-        get_api_url("has_weather")?,
-        substitions,
-    )?;
-    let stations = reqwest::blocking::get(url)?.json::<bool>()?;
-    Ok(stations)
+pub fn get_has_weather<D: Into<Option<NaiveDate>>>(station_number: u32, date: D) -> Result<bool> {
+    HisparcClient::shared().get_has_weather(station_number, date)
 }
 
-pub fn get_has_data(station_number: u32, year: u32, month: u32, day: u32) -> Result<bool> {
-    let mut substitions = HashMap::new();
-    substitions.insert("station_number".to_string(), station_number);
-    substitions.insert("year".to_string(), year);
-    substitions.insert("month".to_string(), month);
-    substitions.insert("day".to_string(), day);
-
-    let url =
-        substitute_variables_with_numbers(get_api_url("has_data")?, substitions)?;
-    let stations = reqwest::blocking::get(url)?.json::<bool>()?;
-    Ok(stations)
+pub fn get_has_data<D: Into<Option<NaiveDate>>>(station_number: u32, date: D) -> Result<bool> {
+    HisparcClient::shared().get_has_data(station_number, date)
 }
 
 pub fn get_clusters_in_country(country_number: u32) -> Result<Vec<NameNumber>> {
-    let mut substitions = HashMap::new();
-    substitions.insert("country_number".to_string(), country_number);
-
-    let url = substitute_variables_with_numbers(
-        get_api_url("clusters_in_country")?,
-        substitions,
-    )?;
-    let stations = reqwest::blocking::get(url)?.json::<Vec<NameNumber>>()?;
-    Ok(stations)
+    HisparcClient::shared().get_clusters_in_country(country_number)
 }
 
 pub fn get_stations_in_subcluster(subcluster_number: u32) -> Result<Vec<NameNumber>> {
-    let mut substitions = HashMap::new();
-    substitions.insert("subcluster_number".to_string(), subcluster_number);
-
-    let url = substitute_variables_with_numbers(
-        get_api_url("stations_in_subcluster")?,
-        substitions,
-    )?;
-    let stations = reqwest::blocking::get(url)?.json::<Vec<NameNumber>>()?;
-    Ok(stations)
+    HisparcClient::shared().get_stations_in_subcluster(subcluster_number)
 }
 
 pub fn get_event_trace(station_number: u64, ext_timestamp: u64) -> Result<Vec<Vec<u32>>> {
-    let mut substitions = HashMap::new();
-    substitions.insert("station_number".to_string(), station_number);
-    substitions.insert("ext_timestamp".to_string(), ext_timestamp);
-
-    let url = substitute_variables_with_numbers(
-        get_api_url("event_trace")?,
-        substitions,
-    )?;
-    let stations = reqwest::blocking::get(url)?.json::<Vec<Vec<u32>>>()?;
-    Ok(stations)
+    HisparcClient::shared().get_event_trace(station_number, ext_timestamp)
 }
 
 pub fn get_stations() -> Result<Vec<NameNumber>> {
-    let stations = reqwest::blocking::get(get_api_url("stations")?)?
-        .json::<Vec<NameNumber>>()?;
-    Ok(stations)
+    HisparcClient::shared().get_stations()
 }
 
 pub fn get_countries() -> Result<Vec<NameNumber>> {
-    let stations = reqwest::blocking::get(get_api_url("countries")?)?
-        .json::<Vec<NameNumber>>()?;
-    Ok(stations)
+    HisparcClient::shared().get_countries()
 }
 
-pub fn get_stations_with_weather(year: u32, month: u32, day: u32) -> Result<Vec<NameNumber>> {
-    let mut substitions = HashMap::new();
-    substitions.insert("year".to_string(), year);
-    substitions.insert("month".to_string(), month);
-    substitions.insert("day".to_string(), day);
-
-    let url = substitute_variables_with_numbers(
-        get_api_url("stations_with_weather")?,
-        substitions,
-    )?;
-    let stations = reqwest::blocking::get(url)?.json::<Vec<NameNumber>>()?;
-    Ok(stations)
+pub fn get_stations_with_weather<D: Into<Option<NaiveDate>>>(date: D) -> Result<Vec<NameNumber>> {
+    HisparcClient::shared().get_stations_with_weather(date)
 }
 
 pub fn get_subclusters() -> Result<Vec<NameNumber>> {
-    let stations = reqwest::blocking::get(get_api_url("subclusters")?)?
-        .json::<Vec<NameNumber>>()?;
-    Ok(stations)
-}
-
-fn substitute_variables_with_numbers<T: Display>(
-    input_str: &str,
-    substitions: HashMap<String, T>,
-) -> Result<String> {
-    let split: Vec<&str> = input_str.split(|c| c == '{' || c == '}').collect();
-
-    let mut output: Vec<String> = Vec::new();
-    for (i, &item) in split.iter().enumerate() {
-        if i % 2 == 0 {
-            output.push(String::from(item));
-        } else {
-            match substitions.get(item) {
-                Some(a) => {
-                    let str_num = a.to_string();
-                    output.push(str_num);
-                }
-                None => return Err(anyhow!("Could not find {item} in substitutions").into()),
-            }
-        }
-    }
-
-    Ok(output.join(""))
+    HisparcClient::shared().get_subclusters()
 }
@@ -1,4 +1,18 @@
+#[cfg(feature = "async")]
+mod async_functions;
+mod catalogue;
+mod config;
+mod discovery;
 mod functions;
+mod network;
+mod requests;
 mod structs;
+mod template;
 
+pub use catalogue::*;
+pub use config::*;
+pub(crate) use discovery::{ApiUrlCache, DISCOVERY_RETRY_INTERVAL};
 pub use functions::*;
+pub use network::*;
+pub use structs::*;
+pub use template::UrlTemplate;
@@ -0,0 +1,195 @@
+use crate::api::structs::NameNumber;
+use crate::client::HisparcClient;
+use crate::concurrent::run_concurrently;
+use crate::error::{Context, HisparcError, Result};
+use serde::{Deserialize, Serialize};
+
+// Requests in flight at once while building the network
+pub(crate) const NETWORK_WORKERS: usize = 8;
+
+// The whole HiSPARC network as a tree of countries, clusters, subclusters and
+// stations. Serializes to JSON, so it can be kept for offline use.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Network {
+    pub countries: Vec<Country>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Country {
+    pub number: u32,
+    pub name: String,
+    pub clusters: Vec<Cluster>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Cluster {
+    pub number: u32,
+    pub name: String,
+    pub subclusters: Vec<Subcluster>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Subcluster {
+    pub number: u32,
+    pub name: String,
+    pub stations: Vec<NameNumber>,
+}
+
+// A station together with everything above it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StationPath<'a> {
+    pub country: &'a Country,
+    pub cluster: &'a Cluster,
+    pub subcluster: &'a Subcluster,
+    pub station: &'a NameNumber,
+}
+
+impl Network {
+    pub fn clusters(&self) -> impl Iterator<Item = &Cluster> {
+        self.countries.iter().flat_map(|x| x.clusters.iter())
+    }
+
+    pub fn subclusters(&self) -> impl Iterator<Item = &Subcluster> {
+        self.clusters().flat_map(|x| x.subclusters.iter())
+    }
+
+    // Every station, in the order of the tree
+    pub fn stations(&self) -> impl Iterator<Item = StationPath<'_>> {
+        self.countries.iter().flat_map(|country| {
+            country.clusters.iter().flat_map(move |cluster| {
+                cluster.subclusters.iter().flat_map(move |subcluster| {
+                    subcluster.stations.iter().map(move |station| StationPath {
+                        country,
+                        cluster,
+                        subcluster,
+                        station,
+                    })
+                })
+            })
+        })
+    }
+
+    pub fn country(&self, number: u32) -> Option<&Country> {
+        self.countries.iter().find(|x| x.number == number)
+    }
+
+    pub fn cluster(&self, number: u32) -> Option<&Cluster> {
+        self.clusters().find(|x| x.number == number)
+    }
+
+    pub fn subcluster(&self, number: u32) -> Option<&Subcluster> {
+        self.subclusters().find(|x| x.number == number)
+    }
+
+    pub fn station(&self, number: u32) -> Option<StationPath<'_>> {
+        self.stations().find(|x| x.station.number == number)
+    }
+
+    pub fn country_of_cluster(&self, cluster_number: u32) -> Option<&Country> {
+        self.countries
+            .iter()
+            .find(|x| x.clusters.iter().any(|x| x.number == cluster_number))
+    }
+
+    pub fn cluster_of_subcluster(&self, subcluster_number: u32) -> Option<&Cluster> {
+        self.clusters()
+            .find(|x| x.subclusters.iter().any(|x| x.number == subcluster_number))
+    }
+
+    pub fn subcluster_of_station(&self, station_number: u32) -> Option<&Subcluster> {
+        self.station(station_number).map(|x| x.subcluster)
+    }
+
+    pub fn cluster_of_station(&self, station_number: u32) -> Option<&Cluster> {
+        self.station(station_number).map(|x| x.cluster)
+    }
+
+    pub fn country_of_station(&self, station_number: u32) -> Option<&Country> {
+        self.station(station_number).map(|x| x.country)
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(self).map_err(HisparcError::parse)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self> {
+        serde_json::from_str(json)
+            .map_err(HisparcError::parse)
+            .context("parsing network")
+    }
+}
+
+impl HisparcClient {
+    // Fetches each level of the tree concurrently, one request per country,
+    // cluster and subcluster
+    pub fn get_network(&self) -> Result<Network> {
+        let countries = self.get_countries()?;
+
+        let clusters = collect(run_concurrently(&countries, NETWORK_WORKERS, |x| {
+            self.get_clusters_in_country(x.number)
+                .context(format!("getting clusters in country {}", x.number))
+        }))?;
+
+        let all_clusters: Vec<&NameNumber> = clusters.iter().flatten().collect();
+        let subclusters = collect(run_concurrently(&all_clusters, NETWORK_WORKERS, |x| {
+            self.get_subclusters_in_cluster(x.number)
+                .context(format!("getting subclusters in cluster {}", x.number))
+        }))?;
+
+        let all_subclusters: Vec<&NameNumber> = subclusters.iter().flatten().collect();
+        let stations = collect(run_concurrently(&all_subclusters, NETWORK_WORKERS, |x| {
+            self.get_stations_in_subcluster(x.number)
+                .context(format!("getting stations in subcluster {}", x.number))
+        }))?;
+
+        Ok(assemble(countries, clusters, subclusters, stations))
+    }
+}
+
+pub fn get_network() -> Result<Network> {
+    HisparcClient::shared().get_network()
+}
+
+fn collect<T>(results: Vec<Result<T>>) -> Result<Vec<T>> {
+    results.into_iter().collect()
+}
+
+// Puts the levels back together. Each level holds one list per entry of the
+// level above, flattened in order.
+pub(crate) fn assemble(
+    countries: Vec<NameNumber>,
+    clusters: Vec<Vec<NameNumber>>,
+    subclusters: Vec<Vec<NameNumber>>,
+    stations: Vec<Vec<NameNumber>>,
+) -> Network {
+    let mut subclusters = subclusters.into_iter();
+    let mut stations = stations.into_iter();
+
+    let countries = countries
+        .into_iter()
+        .zip(clusters)
+        .map(|(country, clusters)| Country {
+            number: country.number,
+            name: country.name,
+            clusters: clusters
+                .into_iter()
+                .zip(subclusters.by_ref())
+                .map(|(cluster, subclusters)| Cluster {
+                    number: cluster.number,
+                    name: cluster.name,
+                    subclusters: subclusters
+                        .into_iter()
+                        .zip(stations.by_ref())
+                        .map(|(subcluster, stations)| Subcluster {
+                            number: subcluster.number,
+                            name: subcluster.name,
+                            stations,
+                        })
+                        .collect(),
+                })
+                .collect(),
+        })
+        .collect();
+
+    Network { countries }
+}
@@ -0,0 +1,208 @@
+use crate::api::config::StationConfig;
+use crate::api::structs::*;
+use crate::api::template::UrlTemplate;
+use crate::error::{HisparcError, Result};
+use chrono::{Datelike, NaiveDate, NaiveDateTime, Timelike, Utc};
+use std::collections::HashMap;
+use std::marker::PhantomData;
+
+// Describes a call to one of the discovered API endpoints, independent of
+// whether it is sent by the blocking or the async client.
+pub(crate) struct ApiRequest<T> {
+    pub(crate) key: &'static str,
+    substitutions: HashMap<&'static str, String>,
+    // Set for Period::All, whose answer keeps changing like today's
+    ongoing: bool,
+    response: PhantomData<T>,
+}
+
+impl<T> ApiRequest<T> {
+    fn new(key: &'static str) -> Self {
+        Self {
+            key,
+            substitutions: HashMap::new(),
+            ongoing: false,
+            response: PhantomData,
+        }
+    }
+
+    fn with<V: ToString>(mut self, name: &'static str, value: V) -> Self {
+        self.substitutions.insert(name, value.to_string());
+        self
+    }
+
+    // Without a date the API answers with the latest values
+    fn with_date(self, date: Option<NaiveDate>) -> Result<Self> {
+        match date {
+            Some(date) => Ok(self
+                .with("year", validate_year(date.year())?)
+                .with("month", date.month())
+                .with("day", date.day())),
+            None => Ok(self),
+        }
+    }
+
+    fn with_period(self, period: Period) -> Result<Self> {
+        match period {
+            Period::All => Ok(Self {
+                ongoing: true,
+                ..self
+            }),
+            Period::Year(year) => Ok(self.with("year", validate_year(year)?)),
+            Period::Month(year, month) => {
+                if !(1..=12).contains(&month) {
+                    return Err(HisparcError::InvalidInput(format!(
+                        "Month {} is not between 1 and 12",
+                        month
+                    )));
+                }
+                Ok(self.with("year", validate_year(year)?).with("month", month))
+            }
+            Period::Day(date) => self.with_date(Some(date)),
+            Period::Hour(datetime) => Ok(self
+                .with_date(Some(datetime.date()))?
+                .with("hour", datetime.hour())),
+        }
+    }
+
+    // The day the response is about, if any. Traces are about the day of
+    // their timestamp, a year or month about its last day and Period::All
+    // about today.
+    pub(crate) fn date(&self) -> Option<NaiveDate> {
+        if self.ongoing {
+            return Some(Utc::now().date_naive());
+        }
+
+        if let Some(ext_timestamp) = self.substitutions.get("ext_timestamp") {
+            let seconds = ext_timestamp.parse::<u64>().ok()? / 1_000_000_000;
+            return NaiveDateTime::from_timestamp_opt(seconds as i64, 0).map(|x| x.date());
+        }
+
+        let year = self.substitutions.get("year")?.parse().ok()?;
+        let month = match self.substitutions.get("month") {
+            Some(month) => month.parse().ok()?,
+            None => 12,
+        };
+
+        match self.substitutions.get("day") {
+            Some(day) => NaiveDate::from_ymd_opt(year, month, day.parse().ok()?),
+            None => last_day_of_month(year, month),
+        }
+    }
+
+    pub(crate) fn url(&self, template: &UrlTemplate) -> Result<String> {
+        template.expand(&self.substitutions)
+    }
+}
+
+pub(crate) fn station_info(
+    station_number: u32,
+    date: Option<NaiveDate>,
+) -> Result<ApiRequest<StationInfo>> {
+    ApiRequest::new("station_info")
+        .with("station_number", station_number)
+        .with_date(date)
+}
+
+pub(crate) fn stations_with_data(date: Option<NaiveDate>) -> Result<ApiRequest<Vec<NameNumber>>> {
+    ApiRequest::new("stations_with_data").with_date(date)
+}
+
+pub(crate) fn has_singles(
+    station_number: u32,
+    date: Option<NaiveDate>,
+) -> Result<ApiRequest<bool>> {
+    ApiRequest::new("has_singles")
+        .with("station_number", station_number)
+        .with_date(date)
+}
+
+pub(crate) fn subclusters_in_cluster(cluster_number: u32) -> ApiRequest<Vec<NameNumber>> {
+    ApiRequest::new("subclusters_in_cluster").with("cluster_number", cluster_number)
+}
+
+pub(crate) fn configuration(
+    station_number: u32,
+    date: Option<NaiveDate>,
+) -> Result<ApiRequest<StationConfig>> {
+    ApiRequest::new("configuration")
+        .with("station_number", station_number)
+        .with_date(date)
+}
+
+pub(crate) fn clusters() -> ApiRequest<Vec<NameNumber>> {
+    ApiRequest::new("clusters")
+}
+
+pub(crate) fn number_of_events(station_number: u32, period: Period) -> Result<ApiRequest<u32>> {
+    ApiRequest::new("number_of_events")
+        .with("station_number", station_number)
+        .with_period(period)
+}
+
+pub(crate) fn has_weather(
+    station_number: u32,
+    date: Option<NaiveDate>,
+) -> Result<ApiRequest<bool>> {
+    ApiRequest::new("has_weather")
+        .with("station_number", station_number)
+        .with_date(date)
+}
+
+pub(crate) fn has_data(station_number: u32, date: Option<NaiveDate>) -> Result<ApiRequest<bool>> {
+    ApiRequest::new("has_data")
+        .with("station_number", station_number)
+        .with_date(date)
+}
+
+pub(crate) fn clusters_in_country(country_number: u32) -> ApiRequest<Vec<NameNumber>> {
+    ApiRequest::new("clusters_in_country").with("country_number", country_number)
+}
+
+pub(crate) fn stations_in_subcluster(subcluster_number: u32) -> ApiRequest<Vec<NameNumber>> {
+    ApiRequest::new("stations_in_subcluster").with("subcluster_number", subcluster_number)
+}
+
+pub(crate) fn event_trace(station_number: u64, ext_timestamp: u64) -> ApiRequest<Vec<Vec<u32>>> {
+    ApiRequest::new("event_trace")
+        .with("station_number", station_number)
+        .with("ext_timestamp", ext_timestamp)
+}
+
+pub(crate) fn stations() -> ApiRequest<Vec<NameNumber>> {
+    ApiRequest::new("stations")
+}
+
+pub(crate) fn countries() -> ApiRequest<Vec<NameNumber>> {
+    ApiRequest::new("countries")
+}
+
+pub(crate) fn stations_with_weather(
+    date: Option<NaiveDate>,
+) -> Result<ApiRequest<Vec<NameNumber>>> {
+    ApiRequest::new("stations_with_weather").with_date(date)
+}
+
+pub(crate) fn subclusters() -> ApiRequest<Vec<NameNumber>> {
+    ApiRequest::new("subclusters")
+}
+
+fn validate_year(year: i32) -> Result<u32> {
+    match year {
+        1..=9999 => Ok(year as u32),
+        _ => Err(HisparcError::InvalidInput(format!(
+            "Year {} is not between 1 and 9999",
+            year
+        ))),
+    }
+}
+
+fn last_day_of_month(year: i32, month: u32) -> Option<NaiveDate> {
+    let (next_year, next_month) = if month == 12 {
+        (year + 1, 1)
+    } else {
+        (year, month + 1)
+    };
+
+    NaiveDate::from_ymd_opt(next_year, next_month, 1)?.pred_opt()
+}
@@ -0,0 +1,185 @@
+use crate::error::{HisparcError, Result};
+use std::collections::HashMap;
+use std::fmt;
+
+// Date parameters may be left out of an endpoint, but only from the end of
+// its template, in which case the URL stops before the first missing one.
+const OPTIONAL_PARAMETERS: &[&str] = &["year", "month", "day", "hour"];
+
+// An endpoint template such as `station/{station_number}/{year}/{month}/{day}/`,
+// parsed once when the endpoints are discovered. Literal braces are written
+// as `{{` and `}}`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UrlTemplate {
+    source: String,
+    parts: Vec<Part>,
+    // Index into parts of the first optional parameter
+    first_optional: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Part {
+    Literal(String),
+    Parameter(String),
+}
+
+impl UrlTemplate {
+    pub fn parse(template: &str) -> Result<Self> {
+        let mut parts = Vec::new();
+        let mut literal = String::new();
+        let mut chars = template.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    literal.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    literal.push('}');
+                }
+                '{' => {
+                    let mut name = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) if c.is_ascii_alphanumeric() || c == '_' => name.push(c),
+                            Some(c) => {
+                                return Err(invalid_template(
+                                    template,
+                                    format!("unexpected {:?} in a parameter name", c),
+                                ))
+                            }
+                            None => return Err(invalid_template(template, "unclosed {")),
+                        }
+                    }
+
+                    if name.is_empty() {
+                        return Err(invalid_template(template, "empty parameter name"));
+                    }
+                    if !literal.is_empty() {
+                        parts.push(Part::Literal(std::mem::take(&mut literal)));
+                    }
+                    parts.push(Part::Parameter(name));
+                }
+                '}' => return Err(invalid_template(template, "unmatched }")),
+                c => literal.push(c),
+            }
+        }
+
+        if !literal.is_empty() {
+            parts.push(Part::Literal(literal));
+        }
+
+        // Only a trailing run of date parameters is optional
+        let mut first_optional = parts.len();
+        for (i, part) in parts.iter().enumerate().rev() {
+            match part {
+                Part::Parameter(name) if OPTIONAL_PARAMETERS.contains(&name.as_str()) => {
+                    first_optional = i;
+                }
+                Part::Parameter(_) => break,
+                Part::Literal(_) => {}
+            }
+        }
+
+        Ok(Self {
+            source: template.to_string(),
+            parts,
+            first_optional,
+        })
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+
+    pub fn parameters(&self) -> impl Iterator<Item = &str> {
+        parameter_names(&self.parts)
+    }
+
+    pub fn required_parameters(&self) -> impl Iterator<Item = &str> {
+        parameter_names(&self.parts[..self.first_optional])
+    }
+
+    pub fn optional_parameters(&self) -> impl Iterator<Item = &str> {
+        parameter_names(&self.parts[self.first_optional..])
+    }
+
+    // Values are percent-encoded. Optional parameters may be left out from
+    // the end only, so a day cannot be given without a month.
+    pub fn expand(&self, values: &HashMap<&str, String>) -> Result<String> {
+        if let Some(unknown) = values.keys().find(|x| !self.parameters().any(|p| p == **x)) {
+            return Err(self.invalid_values(format!("unknown parameter {}", unknown)));
+        }
+
+        let missing: Vec<&str> = self
+            .required_parameters()
+            .filter(|x| !values.contains_key(x))
+            .collect();
+        if !missing.is_empty() {
+            return Err(self.invalid_values(format!("missing {}", missing.join(", "))));
+        }
+
+        let mut url = String::new();
+        let mut left_out: Option<&str> = None;
+
+        for part in &self.parts {
+            match (part, left_out) {
+                (Part::Literal(text), None) => url.push_str(text),
+                (Part::Parameter(name), None) => match values.get(name.as_str()) {
+                    Some(value) => url.push_str(&percent_encode(value)),
+                    None => {
+                        // Drop the start of the segment holding this parameter
+                        url.truncate(url.rfind('/').map_or(0, |i| i + 1));
+                        left_out = Some(name);
+                    }
+                },
+                (Part::Parameter(name), Some(left_out)) if values.contains_key(name.as_str()) => {
+                    return Err(self.invalid_values(format!("{} given without {}", name, left_out)));
+                }
+                (_, Some(_)) => {}
+            }
+        }
+
+        Ok(url)
+    }
+
+    fn invalid_values(&self, reason: String) -> HisparcError {
+        HisparcError::InvalidInput(format!("Cannot fill in {}: {}", self.source, reason))
+    }
+}
+
+impl fmt::Display for UrlTemplate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+fn parameter_names(parts: &[Part]) -> impl Iterator<Item = &str> {
+    parts.iter().filter_map(|part| match part {
+        Part::Parameter(name) => Some(name.as_str()),
+        Part::Literal(_) => None,
+    })
+}
+
+fn invalid_template<S: fmt::Display>(template: &str, reason: S) -> HisparcError {
+    HisparcError::InvalidInput(format!("Invalid URL template {}: {}", template, reason))
+}
+
+// Everything but unreserved characters, as a value is a single path segment
+fn percent_encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                encoded.push(byte as char)
+            }
+            byte => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+
+    encoded
+}
@@ -0,0 +1,252 @@
+use crate::api::ApiUrlCache;
+use crate::cache::ResponseCache;
+use crate::cassette::{Cassette, CassetteMode, Recording};
+use crate::client::{parse_json, parse_text, HisparcClientBuilder};
+use crate::data::DownloadRequest;
+use crate::error::{HisparcError, Result};
+use crate::retry::{RateLimiter, RetryAfter, RetryPolicy};
+use chrono::NaiveDate;
+use reqwest::{Client, Response, StatusCode};
+use serde::de::DeserializeOwned;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+// The async counterpart of HisparcClient, built from the same builder with
+// build_async. Its methods mirror those of HisparcClient.
+#[derive(Clone)]
+pub struct AsyncHisparcClient {
+    pub(crate) api_url: String,
+    pub(crate) data_url: String,
+    http: Client,
+    timeout: Option<Duration>,
+    download_timeout: Option<Duration>,
+    retry_policy: RetryPolicy,
+    rate_limiter: Option<RateLimiter>,
+    cassette: Option<Cassette>,
+    cache: Option<ResponseCache>,
+    pub(crate) api_urls: Arc<ApiUrlCache>,
+}
+
+impl HisparcClientBuilder {
+    pub fn build_async(self) -> Result<AsyncHisparcClient> {
+        // Timeouts are set per request, as API calls and downloads differ
+        let mut builder = Client::builder().user_agent(self.user_agent);
+
+        if let Some(timeout) = self.connect_timeout {
+            builder = builder.connect_timeout(timeout);
+        }
+        if let Some(proxy) = self.proxy {
+            builder = builder.proxy(proxy);
+        }
+        if let Some(max) = self.pool_max_idle_per_host {
+            builder = builder.pool_max_idle_per_host(max);
+        }
+
+        Ok(AsyncHisparcClient {
+            api_url: self.api_url,
+            data_url: self.data_url,
+            http: builder.build().map_err(HisparcError::Client)?,
+            timeout: self.timeout,
+            download_timeout: self.download_timeout,
+            retry_policy: self.retry_policy,
+            rate_limiter: self.rate_limiter,
+            cassette: self.cassette,
+            cache: self.cache,
+            api_urls: Arc::new(ApiUrlCache::new(self.discovery_retry_interval)),
+        })
+    }
+}
+
+impl Default for AsyncHisparcClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AsyncHisparcClient {
+    // Panics if the underlying HTTP client cannot be created, like
+    // reqwest::Client::new. Use the builder to handle that error.
+    pub fn new() -> Self {
+        HisparcClientBuilder::new()
+            .build_async()
+            .expect("failed to build the default AsyncHisparcClient")
+    }
+
+    pub fn builder() -> HisparcClientBuilder {
+        HisparcClientBuilder::new()
+    }
+
+    pub fn api_url(&self) -> &str {
+        &self.api_url
+    }
+
+    pub fn data_url(&self) -> &str {
+        &self.data_url
+    }
+
+    pub fn cassette(&self) -> Option<&Cassette> {
+        self.cassette.as_ref()
+    }
+
+    pub fn cache(&self) -> Option<&ResponseCache> {
+        self.cache.as_ref()
+    }
+
+    // Cache files are small and read with blocking calls, like cassettes
+    pub(crate) async fn get_json_cached<T: DeserializeOwned>(
+        &self,
+        url: &str,
+        date: Option<NaiveDate>,
+    ) -> Result<T> {
+        // A cassette sees every request, so the cache is not used with one
+        let cache = match (&self.cache, &self.cassette) {
+            (Some(cache), None) => cache,
+            _ => return self.get_json(url).await,
+        };
+
+        if let Some(body) = cache.get(url) {
+            return parse_json(&body, url);
+        }
+
+        let value: serde_json::Value = self.get_json(url).await?;
+        let body = serde_json::to_vec(&value).map_err(HisparcError::parse)?;
+
+        // A response that cannot be cached is still a valid response
+        let _ = cache.put(url, &body, cache.ttl_for(date));
+
+        parse_json(&body, url)
+    }
+
+    pub(crate) async fn get_json<T: DeserializeOwned>(&self, url: &str) -> Result<T> {
+        if let Some(cassette) = &self.cassette {
+            let body = self.get_recorded(cassette, url, self.timeout).await?;
+            return parse_json(&body, url);
+        }
+
+        let response = self
+            .send(url, self.timeout)
+            .await
+            .map_err(|e| HisparcError::from_reqwest(e, url))?;
+
+        response
+            .json::<T>()
+            .await
+            .map_err(|e| HisparcError::from_reqwest(e, url))
+    }
+
+    pub(crate) async fn download(&self, download: &DownloadRequest) -> Result<Response> {
+        let url = download.url(&self.data_url)?;
+
+        if let Some(cassette) = &self.cassette {
+            let body = self
+                .get_recorded(cassette, &url, self.download_timeout)
+                .await?;
+            return Ok(http::Response::new(body).into());
+        }
+
+        self.send(&url, self.download_timeout)
+            .await
+            .map_err(|e| HisparcError::from_reqwest(e, &url))
+    }
+
+    pub(crate) async fn download_text(&self, download: &DownloadRequest) -> Result<String> {
+        let url = download.url(&self.data_url)?;
+
+        if let Some(cassette) = &self.cassette {
+            let body = self
+                .get_recorded(cassette, &url, self.download_timeout)
+                .await?;
+            return parse_text(body, &url);
+        }
+
+        let response = self
+            .send(&url, self.download_timeout)
+            .await
+            .map_err(|e| HisparcError::from_reqwest(e, &url))?;
+
+        response
+            .text()
+            .await
+            .map_err(|e| HisparcError::from_reqwest(e, &url))
+    }
+
+    // Cassette files are small and read with blocking calls
+    async fn get_recorded(
+        &self,
+        cassette: &Cassette,
+        url: &str,
+        timeout: Option<Duration>,
+    ) -> Result<Vec<u8>> {
+        let recording = match cassette.mode() {
+            CassetteMode::Replay => cassette.load(url)?,
+            CassetteMode::Record => {
+                let (status, body) = match self.send(url, timeout).await {
+                    Ok(response) => {
+                        let status = response.status().as_u16();
+                        let body = response
+                            .bytes()
+                            .await
+                            .map_err(|e| HisparcError::from_reqwest(e, url))?;
+                        (status, body.to_vec())
+                    }
+                    // A missing resource is recorded like any other answer
+                    Err(e) if e.status() == Some(StatusCode::NOT_FOUND) => (404, Vec::new()),
+                    Err(e) => return Err(HisparcError::from_reqwest(e, url)),
+                };
+
+                cassette.save(url, status, &body)?;
+                Recording::new(status, body)
+            }
+        };
+
+        recording.into_body(url)
+    }
+
+    // A GET of url, retried by the policy. Only getting the response is
+    // retried, not reading its body, as the body may be streamed.
+    async fn send(&self, url: &str, timeout: Option<Duration>) -> reqwest::Result<Response> {
+        self.with_retry(|retry_after| async move {
+            let mut request = self.http.get(url);
+
+            if let Some(timeout) = timeout {
+                request = request.timeout(timeout);
+            }
+
+            let response = request.send().await?;
+            retry_after.observe(response.status(), response.headers());
+            response.error_for_status()
+        })
+        .await
+    }
+
+    async fn with_retry<T, F, Fut>(&self, mut attempt: F) -> reqwest::Result<T>
+    where
+        F: FnMut(RetryAfter) -> Fut,
+        Fut: Future<Output = reqwest::Result<T>>,
+    {
+        let mut retry = 0;
+        let retry_after = RetryAfter::default();
+
+        loop {
+            if let Some(rate_limiter) = &self.rate_limiter {
+                let wait = rate_limiter.reserve();
+                if !wait.is_zero() {
+                    tokio::time::sleep(wait).await;
+                }
+            }
+
+            match attempt(retry_after.clone()).await {
+                Err(e)
+                    if retry + 1 < self.retry_policy.attempts()
+                        && self.retry_policy.should_retry(&e) =>
+                {
+                    retry += 1;
+                    let backoff = self.retry_policy.backoff_with(retry, retry_after.take());
+                    tokio::time::sleep(backoff).await;
+                }
+                result => return result,
+            }
+        }
+    }
+}
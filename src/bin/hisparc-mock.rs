@@ -0,0 +1,21 @@
+use anyhow::{Context, Result};
+use hisparc::mock::MockServer;
+
+// Serves a fixture directory as a stand-in for data.hisparc.nl:
+// hisparc-mock [FIXTURES] [ADDRESS]
+fn main() -> Result<()> {
+    let mut args = std::env::args().skip(1);
+    let fixtures = args.next().unwrap_or_else(|| "tests/fixtures".to_string());
+    let address = args.next().unwrap_or_else(|| "127.0.0.1:8000".to_string());
+
+    let server =
+        MockServer::bind(&address, &fixtures).context(format!("binding to {}", address))?;
+
+    println!("Serving {} on {}", fixtures, server.url());
+    println!("API: {}", server.api_url());
+    println!("Data: {}", server.data_url());
+
+    server.wait();
+
+    Ok(())
+}
@@ -0,0 +1,241 @@
+use crate::cassette::url_hash;
+use crate::error::{Context, HisparcError, Result};
+use chrono::{prelude::DateTime, NaiveDate, NaiveDateTime, Utc};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+// Layout of the cache directory, per cached response:
+// - {hash}.json: the response body
+// - {hash}.meta: one `stored_at\texpires_at\turl` line, with unix seconds and
+//   an empty expires_at for responses that never expire
+//
+// Both files are written under a `.part` name and renamed, the body first, so
+// a response is only visible once it has been stored completely.
+
+const DEFAULT_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+const DEFAULT_TODAY_TTL: Duration = Duration::from_secs(10 * 60);
+
+// A persistent cache of API responses, keyed by URL. Responses about a past
+// date never change and are kept forever. Responses about today or a later
+// date are kept for `today_ttl`, and responses without a date, such as the
+// list of stations, for `ttl`.
+//
+// Cloning is cheap, and clones use the same directory.
+#[derive(Debug, Clone)]
+pub struct ResponseCache {
+    dir: PathBuf,
+    ttl: Duration,
+    today_ttl: Duration,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CacheEntry {
+    pub url: String,
+    pub stored_at: DateTime<Utc>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub size: u64,
+}
+
+impl ResponseCache {
+    pub fn new<P: Into<PathBuf>>(dir: P) -> Self {
+        Self {
+            dir: dir.into(),
+            ttl: DEFAULT_TTL,
+            today_ttl: DEFAULT_TODAY_TTL,
+        }
+    }
+
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    pub fn today_ttl(mut self, ttl: Duration) -> Self {
+        self.today_ttl = ttl;
+        self
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    // Every stored response, including expired ones
+    pub fn entries(&self) -> Result<Vec<CacheEntry>> {
+        let mut entries = Vec::new();
+
+        for file in self.meta_files()? {
+            if let Some(entry) = read_entry(&file) {
+                entries.push(entry);
+            }
+        }
+
+        entries.sort_by(|a, b| a.url.cmp(&b.url));
+        Ok(entries)
+    }
+
+    pub fn get_entry(&self, url: &str) -> Option<CacheEntry> {
+        read_entry(&self.meta_path(url))
+    }
+
+    // Returns whether the URL was cached
+    pub fn remove(&self, url: &str) -> Result<bool> {
+        let meta_path = self.meta_path(url);
+        if !meta_path.exists() {
+            return Ok(false);
+        }
+
+        remove_entry(&meta_path)?;
+        Ok(true)
+    }
+
+    // Removes expired responses and returns how many were removed
+    pub fn purge_expired(&self) -> Result<usize> {
+        let now = Utc::now();
+        let mut removed = 0;
+
+        for file in self.meta_files()? {
+            let expired = match read_entry(&file) {
+                Some(entry) => entry.expires_at.is_some_and(|x| x <= now),
+                None => true,
+            };
+
+            if expired {
+                remove_entry(&file)?;
+                removed += 1;
+            }
+        }
+
+        Ok(removed)
+    }
+
+    // Removes every response and returns how many were removed
+    pub fn purge(&self) -> Result<usize> {
+        let files = self.meta_files()?;
+
+        for file in &files {
+            remove_entry(file)?;
+        }
+
+        Ok(files.len())
+    }
+
+    // Cache failures are treated as misses, so a broken cache never fails a
+    // request that the server can answer.
+    pub(crate) fn get(&self, url: &str) -> Option<Vec<u8>> {
+        let entry = self.get_entry(url)?;
+
+        // Guards against hash collisions
+        if entry.url != url || entry.is_expired() {
+            return None;
+        }
+
+        fs::read(self.body_path(url)).ok()
+    }
+
+    pub(crate) fn put(&self, url: &str, body: &[u8], ttl: Option<Duration>) -> Result<()> {
+        fs::create_dir_all(&self.dir).context(format!("creating {}", self.dir.display()))?;
+
+        let stored_at = Utc::now();
+        let expires_at = match ttl {
+            Some(ttl) => (stored_at.timestamp() + ttl.as_secs() as i64).to_string(),
+            None => String::new(),
+        };
+        let meta = format!("{}\t{}\t{}\n", stored_at.timestamp(), expires_at, url);
+
+        write_atomically(&self.body_path(url), body)?;
+        write_atomically(&self.meta_path(url), meta.as_bytes())
+    }
+
+    // How long to keep a response about the given date
+    pub(crate) fn ttl_for(&self, date: Option<NaiveDate>) -> Option<Duration> {
+        match date {
+            Some(date) if date < Utc::now().date_naive() => None,
+            Some(_) => Some(self.today_ttl),
+            None => Some(self.ttl),
+        }
+    }
+
+    fn meta_files(&self) -> Result<Vec<PathBuf>> {
+        let read_dir = match fs::read_dir(&self.dir) {
+            Ok(read_dir) => read_dir,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e).context(format!("reading {}", self.dir.display())),
+        };
+
+        let mut files = Vec::new();
+        for file in read_dir {
+            let path = file?.path();
+            if path.extension().is_some_and(|x| x == "meta") {
+                files.push(path);
+            }
+        }
+
+        Ok(files)
+    }
+
+    fn body_path(&self, url: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", url_hash(url)))
+    }
+
+    fn meta_path(&self, url: &str) -> PathBuf {
+        self.dir.join(format!("{}.meta", url_hash(url)))
+    }
+}
+
+impl CacheEntry {
+    pub fn is_expired(&self) -> bool {
+        self.expires_at.is_some_and(|x| x <= Utc::now())
+    }
+}
+
+fn read_entry(meta_path: &Path) -> Option<CacheEntry> {
+    let meta = fs::read_to_string(meta_path).ok()?;
+    let split: Vec<&str> = meta.trim_end_matches('\n').splitn(3, '\t').collect();
+
+    if split.len() != 3 {
+        return None;
+    }
+
+    let stored_at = parse_timestamp(split[0])?;
+    let expires_at = match split[1] {
+        "" => None,
+        x => Some(parse_timestamp(x)?),
+    };
+    let size = fs::metadata(meta_path.with_extension("json")).ok()?.len();
+
+    Some(CacheEntry {
+        url: split[2].to_string(),
+        stored_at,
+        expires_at,
+        size,
+    })
+}
+
+fn parse_timestamp(text: &str) -> Option<DateTime<Utc>> {
+    NaiveDateTime::from_timestamp_opt(text.parse().ok()?, 0).map(|x| x.and_utc())
+}
+
+// The meta file goes first, so an entry never outlives its body
+fn remove_entry(meta_path: &Path) -> Result<()> {
+    for path in [meta_path.to_path_buf(), meta_path.with_extension("json")] {
+        match fs::remove_file(&path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                return Err(HisparcError::from(e)).context(format!("removing {}", path.display()))
+            }
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+fn write_atomically(path: &Path, contents: &[u8]) -> Result<()> {
+    let part = path.with_extension(format!(
+        "{}.part",
+        path.extension().unwrap_or_default().to_string_lossy()
+    ));
+
+    fs::write(&part, contents).context(format!("writing {}", part.display()))?;
+    fs::rename(&part, path).context(format!("renaming {}", part.display()))
+}
@@ -0,0 +1,177 @@
+use crate::error::{Context, HisparcError, Result};
+use once_cell::sync::OnceCell;
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+const INDEX_FILE: &str = "index.tsv";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CassetteMode {
+    // Sends requests as usual and saves every response
+    Record,
+    // Serves saved responses and never touches the network
+    Replay,
+}
+
+// A directory of recorded responses, keyed by the full request URL. Each
+// response body is kept in its own file, named after a hash of the URL, and
+// `index.tsv` lists the hash, status and URL of every recording.
+//
+// Only successful responses and 404s are recorded, as other failures say
+// nothing about the data. A recorded 404 is replayed as NotFound.
+//
+// Clones share their state, so one cassette can be given to several clients.
+#[derive(Debug, Clone)]
+pub struct Cassette {
+    dir: PathBuf,
+    mode: CassetteMode,
+    index: Arc<OnceCell<HashMap<String, u16>>>,
+    writer: Arc<Mutex<()>>,
+}
+
+pub(crate) struct Recording {
+    status: u16,
+    body: Vec<u8>,
+}
+
+impl Cassette {
+    pub fn record<P: Into<PathBuf>>(dir: P) -> Self {
+        Self::new(dir.into(), CassetteMode::Record)
+    }
+
+    pub fn replay<P: Into<PathBuf>>(dir: P) -> Self {
+        Self::new(dir.into(), CassetteMode::Replay)
+    }
+
+    fn new(dir: PathBuf, mode: CassetteMode) -> Self {
+        Self {
+            dir,
+            mode,
+            index: Arc::new(OnceCell::new()),
+            writer: Arc::new(Mutex::new(())),
+        }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn mode(&self) -> CassetteMode {
+        self.mode
+    }
+
+    // The URLs in the cassette, in no particular order
+    pub fn urls(&self) -> Result<Vec<String>> {
+        Ok(self.parse_index()?.into_keys().collect())
+    }
+
+    pub(crate) fn load(&self, url: &str) -> Result<Recording> {
+        let status = match self.read_index()?.get(url) {
+            Some(status) => *status,
+            None => {
+                return Err(HisparcError::NotRecorded {
+                    url: url.to_string(),
+                })
+            }
+        };
+
+        let path = self.body_path(url);
+        let body = fs::read(&path).context(format!("reading {}", path.display()))?;
+
+        Ok(Recording { status, body })
+    }
+
+    pub(crate) fn save(&self, url: &str, status: u16, body: &[u8]) -> Result<()> {
+        // Records are appended from several threads when downloading in chunks
+        let _writer = self.writer.lock().unwrap();
+
+        fs::create_dir_all(&self.dir).context(format!("creating {}", self.dir.display()))?;
+
+        let path = self.body_path(url);
+        fs::write(&path, body).context(format!("writing {}", path.display()))?;
+
+        let index_path = self.dir.join(INDEX_FILE);
+        let mut index = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&index_path)
+            .context(format!("opening {}", index_path.display()))?;
+
+        writeln!(index, "{}\t{}\t{}", url_hash(url), status, url)
+            .context(format!("writing {}", index_path.display()))
+    }
+
+    // Read once when replaying, as nothing is added to the cassette then
+    fn read_index(&self) -> Result<&HashMap<String, u16>> {
+        self.index.get_or_try_init(|| self.parse_index())
+    }
+
+    // Later lines win, so a URL recorded twice replays its last response
+    fn parse_index(&self) -> Result<HashMap<String, u16>> {
+        let path = self.dir.join(INDEX_FILE);
+        let text = fs::read_to_string(&path).context(format!("reading {}", path.display()))?;
+
+        let mut index = HashMap::new();
+        for (i, line) in text.lines().enumerate() {
+            if line.is_empty() {
+                continue;
+            }
+
+            let split: Vec<&str> = line.splitn(3, '\t').collect();
+            if split.len() != 3 {
+                return Err(HisparcError::parse(format!(
+                    "Cassette index line {:?} has {} columns, expected 3",
+                    line,
+                    split.len()
+                ))
+                .at_line(i + 1));
+            }
+
+            let status: u16 = split[1]
+                .parse()
+                .map_err(|e| HisparcError::from(e).at_line(i + 1))?;
+            index.insert(split[2].to_string(), status);
+        }
+
+        Ok(index)
+    }
+
+    fn body_path(&self, url: &str) -> PathBuf {
+        self.dir.join(format!("{}.body", url_hash(url)))
+    }
+}
+
+impl Recording {
+    pub(crate) fn new(status: u16, body: Vec<u8>) -> Self {
+        Self { status, body }
+    }
+
+    pub(crate) fn into_body(self, url: &str) -> Result<Vec<u8>> {
+        match self.status {
+            200..=299 => Ok(self.body),
+            404 => Err(HisparcError::NotFound {
+                url: url.to_string(),
+            }),
+            status => Err(HisparcError::Status {
+                url: url.to_string(),
+                status,
+            }),
+        }
+    }
+}
+
+// FNV-1a, which unlike std's hashers is guaranteed to stay the same, so
+// cassettes and caches remain readable by later versions
+pub(crate) fn url_hash(url: &str) -> String {
+    let mut hash: u64 = 0xcbf29ce484222325;
+
+    for byte in url.bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+
+    format!("{:016x}", hash)
+}
@@ -0,0 +1,379 @@
+use crate::api::{ApiUrlCache, DISCOVERY_RETRY_INTERVAL};
+use crate::cache::ResponseCache;
+use crate::cassette::{Cassette, CassetteMode, Recording};
+use crate::data::DownloadRequest;
+use crate::error::{Context, HisparcError, Result};
+use crate::retry::{RateLimiter, RetryAfter, RetryPolicy};
+use chrono::NaiveDate;
+use once_cell::sync::Lazy;
+use reqwest::blocking::{Client, Response};
+use reqwest::{Proxy, StatusCode};
+use serde::de::DeserializeOwned;
+use std::sync::Arc;
+use std::time::Duration;
+
+pub const DEFAULT_API_URL: &str = "https://data.hisparc.nl/api/";
+pub const DEFAULT_DATA_URL: &str = "https://data.hisparc.nl/data/";
+
+const DEFAULT_USER_AGENT: &str = concat!("hisparc/", env!("CARGO_PKG_VERSION"));
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+static DEFAULT_CLIENT: Lazy<HisparcClient> = Lazy::new(HisparcClient::new);
+
+// Cloning a client is cheap, and clones share their connection pool and
+// discovered API URLs.
+#[derive(Clone)]
+pub struct HisparcClient {
+    pub(crate) api_url: String,
+    pub(crate) data_url: String,
+    http: Client,
+    timeout: Option<Duration>,
+    download_timeout: Option<Duration>,
+    retry_policy: RetryPolicy,
+    rate_limiter: Option<RateLimiter>,
+    cassette: Option<Cassette>,
+    cache: Option<ResponseCache>,
+    pub(crate) api_urls: Arc<ApiUrlCache>,
+}
+
+pub struct HisparcClientBuilder {
+    pub(crate) api_url: String,
+    pub(crate) data_url: String,
+    pub(crate) timeout: Option<Duration>,
+    pub(crate) download_timeout: Option<Duration>,
+    pub(crate) connect_timeout: Option<Duration>,
+    pub(crate) user_agent: String,
+    pub(crate) proxy: Option<Proxy>,
+    pub(crate) pool_max_idle_per_host: Option<usize>,
+    pub(crate) retry_policy: RetryPolicy,
+    pub(crate) rate_limiter: Option<RateLimiter>,
+    pub(crate) cassette: Option<Cassette>,
+    pub(crate) cache: Option<ResponseCache>,
+    pub(crate) discovery_retry_interval: Duration,
+}
+
+impl Default for HisparcClientBuilder {
+    fn default() -> Self {
+        Self {
+            api_url: DEFAULT_API_URL.to_string(),
+            data_url: DEFAULT_DATA_URL.to_string(),
+            timeout: Some(DEFAULT_TIMEOUT),
+            download_timeout: None,
+            connect_timeout: None,
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+            proxy: None,
+            pool_max_idle_per_host: None,
+            retry_policy: RetryPolicy::default(),
+            rate_limiter: None,
+            cassette: None,
+            cache: None,
+            discovery_retry_interval: DISCOVERY_RETRY_INTERVAL,
+        }
+    }
+}
+
+impl HisparcClientBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn api_url<S: Into<String>>(mut self, api_url: S) -> Self {
+        self.api_url = with_trailing_slash(api_url.into());
+        self
+    }
+
+    pub fn data_url<S: Into<String>>(mut self, data_url: S) -> Self {
+        self.data_url = with_trailing_slash(data_url.into());
+        self
+    }
+
+    // Applies to API calls. Data downloads can take far longer, so they use
+    // download_timeout instead.
+    pub fn timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    // Covers the whole download including reading the body, so leave unset
+    // when streaming long ranges.
+    pub fn download_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.download_timeout = timeout;
+        self
+    }
+
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    pub fn user_agent<S: Into<String>>(mut self, user_agent: S) -> Self {
+        self.user_agent = user_agent.into();
+        self
+    }
+
+    pub fn proxy(mut self, proxy: Proxy) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
+
+    pub fn pool_max_idle_per_host(mut self, max: usize) -> Self {
+        self.pool_max_idle_per_host = Some(max);
+        self
+    }
+
+    // Applies to API calls and data downloads alike
+    pub fn retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    // Every attempt, including retries, waits for the limiter
+    pub fn rate_limiter(mut self, rate_limiter: RateLimiter) -> Self {
+        self.rate_limiter = Some(rate_limiter);
+        self
+    }
+
+    // Records every response to, or replays every response from, the
+    // cassette. Downloads are read in full when recording.
+    pub fn cassette(mut self, cassette: Cassette) -> Self {
+        self.cassette = Some(cassette);
+        self
+    }
+
+    // Keeps API responses on disk between runs. Data downloads are not
+    // cached, and neither is anything while a cassette is set.
+    pub fn cache(mut self, cache: ResponseCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    // When the API root cannot be reached, the built-in endpoints are used
+    // for this long before the root is tried again. A minute by default.
+    pub fn discovery_retry_interval(mut self, interval: Duration) -> Self {
+        self.discovery_retry_interval = interval;
+        self
+    }
+
+    pub fn build(self) -> Result<HisparcClient> {
+        // Timeouts are set per request, as API calls and downloads differ
+        let mut builder = Client::builder().timeout(None).user_agent(self.user_agent);
+
+        if let Some(timeout) = self.connect_timeout {
+            builder = builder.connect_timeout(timeout);
+        }
+        if let Some(proxy) = self.proxy {
+            builder = builder.proxy(proxy);
+        }
+        if let Some(max) = self.pool_max_idle_per_host {
+            builder = builder.pool_max_idle_per_host(max);
+        }
+
+        Ok(HisparcClient {
+            api_url: self.api_url,
+            data_url: self.data_url,
+            http: builder.build().map_err(HisparcError::Client)?,
+            timeout: self.timeout,
+            download_timeout: self.download_timeout,
+            retry_policy: self.retry_policy,
+            rate_limiter: self.rate_limiter,
+            cassette: self.cassette,
+            cache: self.cache,
+            api_urls: Arc::new(ApiUrlCache::new(self.discovery_retry_interval)),
+        })
+    }
+}
+
+impl Default for HisparcClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HisparcClient {
+    // Panics if the underlying HTTP client cannot be created, like
+    // reqwest::blocking::Client::new. Use the builder to handle that error.
+    pub fn new() -> Self {
+        HisparcClientBuilder::new()
+            .build()
+            .expect("failed to build the default HisparcClient")
+    }
+
+    pub fn builder() -> HisparcClientBuilder {
+        HisparcClientBuilder::new()
+    }
+
+    // The client used by the free functions in `api` and `data`.
+    pub fn shared() -> &'static HisparcClient {
+        &DEFAULT_CLIENT
+    }
+
+    pub fn api_url(&self) -> &str {
+        &self.api_url
+    }
+
+    pub fn data_url(&self) -> &str {
+        &self.data_url
+    }
+
+    pub fn cassette(&self) -> Option<&Cassette> {
+        self.cassette.as_ref()
+    }
+
+    pub fn cache(&self) -> Option<&ResponseCache> {
+        self.cache.as_ref()
+    }
+
+    // How long the response is kept depends on the date it is about
+    pub(crate) fn get_json_cached<T: DeserializeOwned>(
+        &self,
+        url: &str,
+        date: Option<NaiveDate>,
+    ) -> Result<T> {
+        // A cassette sees every request, so the cache is not used with one
+        let cache = match (&self.cache, &self.cassette) {
+            (Some(cache), None) => cache,
+            _ => return self.get_json(url),
+        };
+
+        if let Some(body) = cache.get(url) {
+            return parse_json(&body, url);
+        }
+
+        let value: serde_json::Value = self.get_json(url)?;
+        let body = serde_json::to_vec(&value).map_err(HisparcError::parse)?;
+
+        // A response that cannot be cached is still a valid response
+        let _ = cache.put(url, &body, cache.ttl_for(date));
+
+        parse_json(&body, url)
+    }
+
+    pub(crate) fn get_json<T: DeserializeOwned>(&self, url: &str) -> Result<T> {
+        if let Some(cassette) = &self.cassette {
+            let body = self.get_recorded(cassette, url, self.timeout)?;
+            return parse_json(&body, url);
+        }
+
+        self.send(url, self.timeout)
+            .and_then(|response| response.json::<T>())
+            .map_err(|e| HisparcError::from_reqwest(e, url))
+    }
+
+    pub(crate) fn download(&self, download: &DownloadRequest) -> Result<Response> {
+        let url = download.url(&self.data_url)?;
+
+        if let Some(cassette) = &self.cassette {
+            let body = self.get_recorded(cassette, &url, self.download_timeout)?;
+            return Ok(http::Response::new(body).into());
+        }
+
+        self.send(&url, self.download_timeout)
+            .map_err(|e| HisparcError::from_reqwest(e, &url))
+    }
+
+    pub(crate) fn download_text(&self, download: &DownloadRequest) -> Result<String> {
+        let url = download.url(&self.data_url)?;
+
+        if let Some(cassette) = &self.cassette {
+            let body = self.get_recorded(cassette, &url, self.download_timeout)?;
+            return parse_text(body, &url);
+        }
+
+        self.send(&url, self.download_timeout)
+            .and_then(|response| response.text())
+            .map_err(|e| HisparcError::from_reqwest(e, &url))
+    }
+
+    fn get_recorded(
+        &self,
+        cassette: &Cassette,
+        url: &str,
+        timeout: Option<Duration>,
+    ) -> Result<Vec<u8>> {
+        let recording = match cassette.mode() {
+            CassetteMode::Replay => cassette.load(url)?,
+            CassetteMode::Record => {
+                let (status, body) = match self.send(url, timeout) {
+                    Ok(response) => {
+                        let status = response.status().as_u16();
+                        let body = response
+                            .bytes()
+                            .map_err(|e| HisparcError::from_reqwest(e, url))?;
+                        (status, body.to_vec())
+                    }
+                    // A missing resource is recorded like any other answer
+                    Err(e) if e.status() == Some(StatusCode::NOT_FOUND) => (404, Vec::new()),
+                    Err(e) => return Err(HisparcError::from_reqwest(e, url)),
+                };
+
+                cassette.save(url, status, &body)?;
+                Recording::new(status, body)
+            }
+        };
+
+        recording.into_body(url)
+    }
+
+    // A GET of url, retried by the policy. Only getting the response is
+    // retried, not reading its body, as the body may be streamed.
+    fn send(&self, url: &str, timeout: Option<Duration>) -> reqwest::Result<Response> {
+        self.with_retry(|retry_after| {
+            let mut request = self.http.get(url);
+
+            if let Some(timeout) = timeout {
+                request = request.timeout(timeout);
+            }
+
+            let response = request.send()?;
+            retry_after.observe(response.status(), response.headers());
+            response.error_for_status()
+        })
+    }
+
+    // A Retry-After header on a failed response takes the place of the
+    // backoff, if the attempt notes it.
+    fn with_retry<T, F>(&self, mut attempt: F) -> reqwest::Result<T>
+    where
+        F: FnMut(RetryAfter) -> reqwest::Result<T>,
+    {
+        let mut retry = 0;
+        let retry_after = RetryAfter::default();
+
+        loop {
+            if let Some(rate_limiter) = &self.rate_limiter {
+                rate_limiter.wait();
+            }
+
+            match attempt(retry_after.clone()) {
+                Err(e)
+                    if retry + 1 < self.retry_policy.attempts()
+                        && self.retry_policy.should_retry(&e) =>
+                {
+                    retry += 1;
+                    std::thread::sleep(self.retry_policy.backoff_with(retry, retry_after.take()));
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+pub(crate) fn parse_json<T: DeserializeOwned>(body: &[u8], url: &str) -> Result<T> {
+    serde_json::from_slice(body).map_err(|e| HisparcError::Json {
+        url: url.to_string(),
+        source: Box::new(e),
+    })
+}
+
+pub(crate) fn parse_text(body: Vec<u8>, url: &str) -> Result<String> {
+    String::from_utf8(body)
+        .map_err(HisparcError::parse)
+        .context(format!("unexpected response from {}", url))
+}
+
+fn with_trailing_slash(mut url: String) -> String {
+    if !url.ends_with('/') {
+        url.push('/');
+    }
+    url
+}
@@ -0,0 +1,41 @@
+use crate::error::{HisparcError, Result};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+// Runs task on every item with up to max_workers threads, and returns the
+// results in the order of the items.
+pub(crate) fn run_concurrently<I, T, F>(items: &[I], max_workers: usize, task: F) -> Vec<Result<T>>
+where
+    I: Sync,
+    T: Send,
+    F: Fn(&I) -> Result<T> + Sync,
+{
+    let next_item = AtomicUsize::new(0);
+    let results: Mutex<Vec<Option<Result<T>>>> = Mutex::new(items.iter().map(|_| None).collect());
+
+    let workers = max_workers.clamp(1, items.len().max(1));
+
+    thread::scope(|scope| {
+        for _ in 0..workers {
+            scope.spawn(|| loop {
+                let i = next_item.fetch_add(1, Ordering::SeqCst);
+                if i >= items.len() {
+                    break;
+                }
+
+                let result = task(&items[i]);
+                results.lock().unwrap()[i] = Some(result);
+            });
+        }
+    });
+
+    results
+        .into_inner()
+        .unwrap()
+        .into_iter()
+        .map(|x| {
+            x.unwrap_or_else(|| Err(HisparcError::InvalidInput("task was never run".to_string())))
+        })
+        .collect()
+}
@@ -0,0 +1,129 @@
+use crate::async_client::AsyncHisparcClient;
+use crate::data::requests;
+use crate::data::structs::*;
+use crate::error::Result;
+use chrono::{prelude::DateTime, Utc};
+use futures_util::TryStreamExt;
+use std::io;
+use std::pin::Pin;
+use tokio::io::{AsyncBufRead, BufReader};
+use tokio_util::io::StreamReader;
+
+pub type ResponseEventStream = AsyncEventStream<Pin<Box<dyn AsyncBufRead + Send>>>;
+
+impl AsyncHisparcClient {
+    pub async fn get_event_data(
+        &self,
+        station_number: u32,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<Event>> {
+        self.get_event_stream(station_number, start, end)
+            .await?
+            .collect_events()
+            .await
+    }
+
+    pub async fn get_event_data_with_metadata(
+        &self,
+        station_number: u32,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<(DownloadMetadata, Vec<Event>)> {
+        let mut stream = self.get_event_stream(station_number, start, end).await?;
+
+        let events = stream.collect_events().await;
+
+        Ok((stream.into_metadata(), events?))
+    }
+
+    pub async fn get_event_stream(
+        &self,
+        station_number: u32,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<ResponseEventStream> {
+        let response = self
+            .download(&requests::events(station_number, start, end))
+            .await?;
+
+        let body = response.bytes_stream().map_err(io::Error::other);
+        let reader: Pin<Box<dyn AsyncBufRead + Send>> =
+            Box::pin(BufReader::new(StreamReader::new(body)));
+
+        Ok(AsyncEventStream::new(reader))
+    }
+
+    pub async fn get_weather_data(
+        &self,
+        station_number: u32,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<WeatherRecord>> {
+        let text = self
+            .download_text(&requests::weather(station_number, start, end))
+            .await?;
+
+        requests::parse_records(&text, WeatherRecord::from_tsv)
+    }
+
+    pub async fn get_singles_data(
+        &self,
+        station_number: u32,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<SinglesRecord>> {
+        let text = self
+            .download_text(&requests::singles(station_number, start, end))
+            .await?;
+
+        requests::parse_records(&text, SinglesRecord::from_tsv)
+    }
+
+    pub async fn has_singles(&self, station_number: u32, date: DateTime<Utc>) -> Result<bool> {
+        self.get_has_singles(station_number, date.date_naive())
+            .await
+    }
+
+    pub async fn has_singles_in_range(
+        &self,
+        station_number: u32,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<bool> {
+        for day in requests::days_in_range(start, end) {
+            if self.has_singles(station_number, day).await? {
+                return Ok(true);
+            }
+        }
+
+        Ok(false)
+    }
+
+    pub async fn get_lightning_data(
+        &self,
+        kind: LightningType,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<LightningStrike>> {
+        let text = self
+            .download_text(&requests::lightning(kind, start, end))
+            .await?;
+
+        requests::parse_records(&text, LightningStrike::from_tsv)
+    }
+
+    pub async fn get_coincidences(
+        &self,
+        stations_or_cluster: StationSelection,
+        n: u32,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<Coincidence>> {
+        let text = self
+            .download_text(&requests::coincidences(&stations_or_cluster, n, start, end))
+            .await?;
+
+        requests::parse_coincidences(&text)
+    }
+}
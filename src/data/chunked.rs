@@ -0,0 +1,117 @@
+use crate::client::HisparcClient;
+use crate::concurrent::run_concurrently;
+use crate::data::structs::*;
+use crate::error::{Context, HisparcError, Result};
+use chrono::{prelude::DateTime, Duration, Utc};
+
+pub struct ChunkedDownloader {
+    client: HisparcClient,
+    window: Duration,
+    max_workers: usize,
+}
+
+impl Default for ChunkedDownloader {
+    fn default() -> Self {
+        Self {
+            client: HisparcClient::shared().clone(),
+            window: Duration::days(1),
+            max_workers: 4,
+        }
+    }
+}
+
+impl ChunkedDownloader {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn client(mut self, client: HisparcClient) -> Self {
+        self.client = client;
+        self
+    }
+
+    pub fn window(mut self, window: Duration) -> Self {
+        self.window = window;
+        self
+    }
+
+    pub fn max_workers(mut self, max_workers: usize) -> Self {
+        self.max_workers = max_workers;
+        self
+    }
+
+    pub fn window_size(&self) -> Duration {
+        self.window
+    }
+
+    pub(crate) fn hisparc_client(&self) -> &HisparcClient {
+        &self.client
+    }
+
+    pub fn get_event_data(
+        &self,
+        station_number: u32,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<Event>> {
+        let windows = split_time_range(start, end, self.window)?;
+
+        let results = self.run_windows(&windows, |(window_start, window_end)| {
+            self.client
+                .get_event_data(station_number, window_start, window_end)
+                .context(format!(
+                    "downloading events of station {} from {} to {}",
+                    station_number, window_start, window_end
+                ))
+        });
+
+        let mut events = Vec::new();
+        for result in results {
+            events.extend(result?);
+        }
+
+        Ok(events)
+    }
+
+    pub(crate) fn run_windows<T, F>(
+        &self,
+        windows: &[(DateTime<Utc>, DateTime<Utc>)],
+        download: F,
+    ) -> Vec<Result<T>>
+    where
+        T: Send,
+        F: Fn((DateTime<Utc>, DateTime<Utc>)) -> Result<T> + Sync,
+    {
+        run_concurrently(windows, self.max_workers, |window| download(*window))
+    }
+}
+
+pub fn split_time_range(
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    window: Duration,
+) -> Result<Vec<(DateTime<Utc>, DateTime<Utc>)>> {
+    if window <= Duration::zero() {
+        return Err(HisparcError::InvalidInput(format!(
+            "Window {} must be positive",
+            window
+        )));
+    }
+    if end < start {
+        return Err(HisparcError::InvalidInput(format!(
+            "End {} is before start {}",
+            end, start
+        )));
+    }
+
+    let mut windows = Vec::new();
+    let mut window_start = start;
+
+    while window_start < end {
+        let window_end = (window_start + window).min(end);
+        windows.push((window_start, window_end));
+        window_start = window_end;
+    }
+
+    Ok(windows)
+}
@@ -1,50 +1,217 @@
+use crate::client::HisparcClient;
+use crate::data::requests;
 use crate::data::structs::*;
-use anyhow::Result;
+use crate::error::{Context, Result};
 use chrono::{prelude::DateTime, Utc};
-use reqwest::blocking::Client;
+use flate2::bufread::MultiGzDecoder;
+use reqwest::blocking::Response;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read};
+use std::path::Path;
 
-// `https://data.hisparc.nl/data/download/?data_type=events&station_events=197&start=2023-5-17&end=2023-5-20`
+impl HisparcClient {
+    pub fn get_event_data(
+        &self,
+        station_number: u32,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<Event>> {
+        self.get_event_stream(station_number, start, end)?.collect()
+    }
 
-const BASE_URL: &str = "https://data.hisparc.nl/data/download/";
+    pub fn get_event_data_with_metadata(
+        &self,
+        station_number: u32,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<(DownloadMetadata, Vec<Event>)> {
+        let mut stream = self.get_event_stream(station_number, start, end)?;
+
+        let events: Result<Vec<Event>> = stream.by_ref().collect();
+
+        Ok((stream.into_metadata(), events?))
+    }
+
+    pub fn get_event_stream(
+        &self,
+        station_number: u32,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<EventStream<BufReader<Response>>> {
+        let response = self.get_event_response(station_number, start, end)?;
+
+        Ok(EventStream::new(BufReader::new(response)))
+    }
+
+    pub(crate) fn get_event_response(
+        &self,
+        station_number: u32,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Response> {
+        self.download(&requests::events(station_number, start, end))
+    }
+
+    pub fn get_weather_data(
+        &self,
+        station_number: u32,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<WeatherRecord>> {
+        let text = self.download_text(&requests::weather(station_number, start, end))?;
+
+        requests::parse_records(&text, WeatherRecord::from_tsv)
+    }
+
+    pub fn get_singles_data(
+        &self,
+        station_number: u32,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<SinglesRecord>> {
+        let text = self.download_text(&requests::singles(station_number, start, end))?;
+
+        requests::parse_records(&text, SinglesRecord::from_tsv)
+    }
+
+    pub fn has_singles(&self, station_number: u32, date: DateTime<Utc>) -> Result<bool> {
+        self.get_has_singles(station_number, date.date_naive())
+    }
+
+    pub fn has_singles_in_range(
+        &self,
+        station_number: u32,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<bool> {
+        for day in requests::days_in_range(start, end) {
+            if self.has_singles(station_number, day)? {
+                return Ok(true);
+            }
+        }
+
+        Ok(false)
+    }
+
+    pub fn get_lightning_data(
+        &self,
+        kind: LightningType,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<LightningStrike>> {
+        let text = self.download_text(&requests::lightning(kind, start, end))?;
+
+        requests::parse_records(&text, LightningStrike::from_tsv)
+    }
+
+    pub fn get_coincidences(
+        &self,
+        stations_or_cluster: StationSelection,
+        n: u32,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<Coincidence>> {
+        let text =
+            self.download_text(&requests::coincidences(&stations_or_cluster, n, start, end))?;
+
+        requests::parse_coincidences(&text)
+    }
+}
 
 pub fn get_event_data(
     station_number: u32,
     start: DateTime<Utc>,
     end: DateTime<Utc>,
 ) -> Result<Vec<Event>> {
-    let station_num_str = station_number.to_string();
+    HisparcClient::shared().get_event_data(station_number, start, end)
+}
 
-    let start_string: String = format!("{}", start.format("%Y-%m-%d %H:%M:%S"));
-    let end_string: String = format!("{}", end.format("%Y-%m-%d %H:%M:%S"));
+pub fn get_event_data_with_metadata(
+    station_number: u32,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Result<(DownloadMetadata, Vec<Event>)> {
+    HisparcClient::shared().get_event_data_with_metadata(station_number, start, end)
+}
 
-    println!("{}", start_string);
-    println!("{}", end_string);
+pub fn get_event_stream(
+    station_number: u32,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Result<EventStream<BufReader<Response>>> {
+    HisparcClient::shared().get_event_stream(station_number, start, end)
+}
 
-    let query = vec![
-        ("data_type", "events"),
-        ("station_events", &station_num_str),
-        ("start", &start_string),
-        ("end", &end_string),
-    ];
+pub fn get_weather_data(
+    station_number: u32,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Result<Vec<WeatherRecord>> {
+    HisparcClient::shared().get_weather_data(station_number, start, end)
+}
 
-    let client = Client::new();
+pub fn get_singles_data(
+    station_number: u32,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Result<Vec<SinglesRecord>> {
+    HisparcClient::shared().get_singles_data(station_number, start, end)
+}
 
-    let response = client.get(BASE_URL).query(&query).send()?;
+pub fn has_singles(station_number: u32, date: DateTime<Utc>) -> Result<bool> {
+    HisparcClient::shared().has_singles(station_number, date)
+}
 
-    let text = response.text()?;
+pub fn has_singles_in_range(
+    station_number: u32,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Result<bool> {
+    HisparcClient::shared().has_singles_in_range(station_number, start, end)
+}
 
-    let lines_iter = text.lines();
+pub fn get_lightning_data(
+    kind: LightningType,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Result<Vec<LightningStrike>> {
+    HisparcClient::shared().get_lightning_data(kind, start, end)
+}
 
-    let filtered_lines_iter = lines_iter.filter(|&x| -> bool {
-        let t = String::from(x);
-        !t.starts_with('#')
-    });
+pub fn get_coincidences(
+    stations_or_cluster: StationSelection,
+    n: u32,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Result<Vec<Coincidence>> {
+    HisparcClient::shared().get_coincidences(stations_or_cluster, n, start, end)
+}
 
-    let parsed_lines_iter = filtered_lines_iter.map(|x| -> Result<Event> { Event::from_tsv(x) });
+pub fn read_events_from_path<P: AsRef<Path>>(path: P) -> Result<Vec<Event>> {
+    let path = path.as_ref();
+    let file = File::open(path).context(format!("opening {}", path.display()))?;
 
-    let parsed_lines: Result<Vec<Event>> = parsed_lines_iter.collect();
+    read_events_from_reader(file).context(format!("reading events from {}", path.display()))
+}
 
-    parsed_lines
+pub fn read_events_from_reader<R: Read>(reader: R) -> Result<Vec<Event>> {
+    event_stream_from_reader(reader)?.collect()
 }
 
-// https://data.hisparc.nl/data/4/weather/?download=True&start=2020-01-23+00%3A00%3A00&end=2020-01-23+00%3A05%3A00
+// Gzipped input is recognised by its magic bytes rather than a file
+// extension, so the same applies to readers.
+pub fn event_stream_from_reader<'a, R: Read + 'a>(
+    reader: R,
+) -> Result<EventStream<Box<dyn BufRead + 'a>>> {
+    let mut reader = BufReader::new(reader);
+
+    let is_gzip = reader.fill_buf()?.starts_with(&[0x1f, 0x8b]);
+
+    let reader: Box<dyn BufRead + 'a> = if is_gzip {
+        Box::new(BufReader::new(MultiGzDecoder::new(reader)))
+    } else {
+        Box::new(reader)
+    };
+
+    Ok(EventStream::new(reader))
+}
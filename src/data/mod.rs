@@ -1,4 +1,15 @@
+#[cfg(feature = "async")]
+mod async_functions;
+mod chunked;
 mod functions;
+mod requests;
+mod resumable;
 mod structs;
 
+#[cfg(feature = "async")]
+pub use async_functions::ResponseEventStream;
+pub use chunked::*;
 pub use functions::*;
+pub(crate) use requests::DownloadRequest;
+pub use resumable::*;
+pub use structs::*;
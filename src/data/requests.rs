@@ -0,0 +1,174 @@
+use crate::data::structs::*;
+use crate::error::{HisparcError, Result};
+use chrono::{prelude::DateTime, Duration, NaiveTime, Utc};
+
+// Relative to the client's data URL, `https://data.hisparc.nl/data/` by default
+const DOWNLOAD_PATH: &str = "download/";
+const COINCIDENCES_PATH: &str = "network/coincidences/";
+
+// Describes a data download, independent of whether it is sent by the
+// blocking or the async client.
+pub(crate) struct DownloadRequest {
+    pub(crate) path: &'static str,
+    pub(crate) query: Vec<(&'static str, String)>,
+}
+
+impl DownloadRequest {
+    // The full URL including the query, which also keys cassette recordings
+    pub(crate) fn url(&self, data_url: &str) -> Result<String> {
+        let url = format!("{}{}", data_url, self.path);
+
+        reqwest::Url::parse_with_params(&url, &self.query)
+            .map(String::from)
+            .map_err(|e| HisparcError::InvalidInput(format!("Invalid data URL {}: {}", url, e)))
+    }
+}
+
+// `https://data.hisparc.nl/data/download/?data_type=events&station_events=197&start=2023-5-17&end=2023-5-20`
+
+pub(crate) fn events(
+    station_number: u32,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> DownloadRequest {
+    station_download("events", station_number, start, end)
+}
+
+// `https://data.hisparc.nl/data/download/?data_type=weather&station_weather=4&start=2020-01-23&end=2020-01-24`
+
+pub(crate) fn weather(
+    station_number: u32,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> DownloadRequest {
+    station_download("weather", station_number, start, end)
+}
+
+// `https://data.hisparc.nl/data/download/?data_type=singles&station_singles=501&start=2023-5-17&end=2023-5-18`
+
+pub(crate) fn singles(
+    station_number: u32,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> DownloadRequest {
+    station_download("singles", station_number, start, end)
+}
+
+// `https://data.hisparc.nl/data/download/?data_type=lightning&lightning_type=4&start=2023-5-17&end=2023-5-20`
+
+pub(crate) fn lightning(
+    kind: LightningType,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> DownloadRequest {
+    DownloadRequest {
+        path: DOWNLOAD_PATH,
+        query: vec![
+            ("data_type", "lightning".to_string()),
+            ("lightning_type", kind.code().to_string()),
+            ("start", format_datetime(start)),
+            ("end", format_datetime(end)),
+        ],
+    }
+}
+
+// `https://data.hisparc.nl/data/network/coincidences/?cluster=Amsterdam&n=3&start=2023-5-17&end=2023-5-18&download=True`
+
+pub(crate) fn coincidences(
+    stations_or_cluster: &StationSelection,
+    n: u32,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> DownloadRequest {
+    let mut query = vec![
+        ("n", n.to_string()),
+        ("start", format_datetime(start)),
+        ("end", format_datetime(end)),
+        ("download", "True".to_string()),
+    ];
+
+    match stations_or_cluster {
+        StationSelection::Network => {}
+        StationSelection::Cluster(cluster) => query.push(("cluster", cluster.clone())),
+        StationSelection::Stations(stations) => {
+            let stations_string = stations
+                .iter()
+                .map(|x| x.to_string())
+                .collect::<Vec<String>>()
+                .join(",");
+            query.push(("stations", stations_string));
+        }
+    }
+
+    DownloadRequest {
+        path: COINCIDENCES_PATH,
+        query,
+    }
+}
+
+// Errors carry the line number within the whole download
+pub(crate) fn parse_records<T>(text: &str, parse: fn(&str) -> Result<T>) -> Result<Vec<T>> {
+    data_lines(text)
+        .map(|(line_number, line)| parse(line).map_err(|e| e.at_line(line_number)))
+        .collect()
+}
+
+pub(crate) fn parse_coincidences(text: &str) -> Result<Vec<Coincidence>> {
+    Coincidence::from_tsv_lines(text.lines())
+}
+
+// Every day touched by the range, as midnight UTC. The end is exclusive, as
+// in the downloads, so an end at midnight leaves out its own day.
+pub(crate) fn days_in_range(start: DateTime<Utc>, end: DateTime<Utc>) -> Vec<DateTime<Utc>> {
+    let mut days = Vec::new();
+    let mut day = start.date_naive();
+    let mut last_day = end.date_naive();
+
+    if end > start && end.time() == NaiveTime::MIN {
+        last_day -= Duration::days(1);
+    }
+
+    while day <= last_day {
+        days.push(day.and_hms_opt(0, 0, 0).unwrap().and_utc());
+        day += Duration::days(1);
+    }
+
+    days
+}
+
+fn station_download(
+    data_type: &str,
+    station_number: u32,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> DownloadRequest {
+    DownloadRequest {
+        path: DOWNLOAD_PATH,
+        query: vec![
+            ("data_type", data_type.to_string()),
+            (station_parameter(data_type), station_number.to_string()),
+            ("start", format_datetime(start)),
+            ("end", format_datetime(end)),
+        ],
+    }
+}
+
+fn station_parameter(data_type: &str) -> &'static str {
+    match data_type {
+        "weather" => "station_weather",
+        "singles" => "station_singles",
+        _ => "station_events",
+    }
+}
+
+fn format_datetime(datetime: DateTime<Utc>) -> String {
+    format!("{}", datetime.format("%Y-%m-%d %H:%M:%S"))
+}
+
+// Lines holding data, numbered from 1 like the lines of the file
+fn data_lines(text: &str) -> impl Iterator<Item = (usize, &str)> {
+    text.lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line))
+        .filter(|(_, x)| !x.starts_with('#') && !x.is_empty())
+}
@@ -0,0 +1,208 @@
+use crate::data::chunked::{split_time_range, ChunkedDownloader};
+use crate::data::structs::*;
+use crate::error::{Context, HisparcError, Result};
+use chrono::{prelude::DateTime, Utc};
+use std::collections::HashSet;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+// Layout of the job directory:
+// - checkpoint.tsv: a header line describing the job, followed by one
+//   `start\tend` line (unix seconds) per completed window
+// - events_{start}_{end}.tsv: the raw download of each completed window
+//
+// A window file is written under a `.part` name and renamed once the download
+// has finished, and only then is the window appended to the checkpoint, so an
+// interrupted window is simply downloaded again on the next run.
+
+const CHECKPOINT_FILE: &str = "checkpoint.tsv";
+
+pub struct ResumableDownload {
+    station_number: u32,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    directory: PathBuf,
+    downloader: ChunkedDownloader,
+}
+
+impl ResumableDownload {
+    pub fn new<P: Into<PathBuf>>(
+        station_number: u32,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        directory: P,
+    ) -> Self {
+        Self {
+            station_number,
+            start,
+            end,
+            directory: directory.into(),
+            downloader: ChunkedDownloader::new(),
+        }
+    }
+
+    pub fn downloader(mut self, downloader: ChunkedDownloader) -> Self {
+        self.downloader = downloader;
+        self
+    }
+
+    pub fn windows(&self) -> Result<Vec<(DateTime<Utc>, DateTime<Utc>)>> {
+        split_time_range(self.start, self.end, self.downloader.window_size())
+    }
+
+    pub fn completed_windows(&self) -> Result<HashSet<(i64, i64)>> {
+        let path = self.directory.join(CHECKPOINT_FILE);
+
+        if !path.exists() {
+            return Ok(HashSet::new());
+        }
+
+        let file = File::open(&path).context(format!("opening {}", path.display()))?;
+        let mut lines = BufReader::new(file).lines();
+
+        match lines.next() {
+            Some(header) => {
+                let header = header?;
+                if header != self.checkpoint_header() {
+                    return Err(HisparcError::InvalidInput(format!(
+                        "Checkpoint {} belongs to a different job: {:?}",
+                        path.display(),
+                        header
+                    )));
+                }
+            }
+            None => return Ok(HashSet::new()),
+        }
+
+        let mut completed = HashSet::new();
+        for line in lines {
+            let line = line?;
+            let split: Vec<&str> = line.split('\t').collect();
+
+            // A torn final line from an interrupted write is not a completed window
+            if split.len() != 2 {
+                continue;
+            }
+            if let (Ok(start), Ok(end)) = (split[0].parse(), split[1].parse()) {
+                if self.window_path(start, end).exists() {
+                    completed.insert((start, end));
+                }
+            }
+        }
+
+        Ok(completed)
+    }
+
+    pub fn run(&self) -> Result<Vec<Event>> {
+        fs::create_dir_all(&self.directory)
+            .context(format!("creating {}", self.directory.display()))?;
+
+        let windows = self.windows()?;
+        let completed = self.completed_windows()?;
+
+        let missing: Vec<(DateTime<Utc>, DateTime<Utc>)> = windows
+            .iter()
+            .filter(|(start, end)| !completed.contains(&(start.timestamp(), end.timestamp())))
+            .cloned()
+            .collect();
+
+        let checkpoint = Mutex::new(self.open_checkpoint(completed.is_empty())?);
+
+        let results = self.downloader.run_windows(&missing, |(start, end)| {
+            self.download_window(start, end)?;
+
+            let mut file = checkpoint.lock().unwrap();
+            writeln!(file, "{}\t{}", start.timestamp(), end.timestamp())?;
+            file.flush()?;
+            Ok(())
+        });
+
+        for result in results {
+            result?;
+        }
+
+        let mut events = Vec::new();
+        for (start, end) in windows {
+            let path = self.window_path(start.timestamp(), end.timestamp());
+            let file = File::open(&path).context(format!("opening {}", path.display()))?;
+
+            for event in EventStream::new(BufReader::new(file)) {
+                events.push(event.context(format!("parsing {}", path.display()))?);
+            }
+        }
+
+        Ok(events)
+    }
+
+    fn download_window(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> Result<()> {
+        let path = self.window_path(start.timestamp(), end.timestamp());
+        let part_path = path.with_extension("tsv.part");
+
+        let mut response = self
+            .downloader
+            .hisparc_client()
+            .get_event_response(self.station_number, start, end)
+            .context(format!(
+                "downloading events of station {} from {} to {}",
+                self.station_number, start, end
+            ))?;
+
+        let mut writer = BufWriter::new(
+            File::create(&part_path).context(format!("creating {}", part_path.display()))?,
+        );
+        io::copy(&mut response, &mut writer)?;
+        writer
+            .into_inner()
+            .map_err(|e| e.into_error())?
+            .sync_all()?;
+
+        fs::rename(&part_path, &path).context(format!("renaming {}", part_path.display()))?;
+
+        Ok(())
+    }
+
+    fn open_checkpoint(&self, fresh: bool) -> Result<File> {
+        let path = self.directory.join(CHECKPOINT_FILE);
+
+        if fresh {
+            let mut file = File::create(&path).context(format!("creating {}", path.display()))?;
+            writeln!(file, "{}", self.checkpoint_header())?;
+            Ok(file)
+        } else {
+            let mut file = OpenOptions::new()
+                .read(true)
+                .append(true)
+                .open(&path)
+                .context(format!("opening {}", path.display()))?;
+
+            // End a torn final line first, or the next window would be
+            // appended to it and lost
+            let mut last = [0u8];
+            if file.metadata()?.len() > 0 {
+                file.seek(SeekFrom::End(-1))?;
+                file.read_exact(&mut last)?;
+                if last[0] != b'\n' {
+                    writeln!(file)?;
+                }
+            }
+
+            Ok(file)
+        }
+    }
+
+    fn checkpoint_header(&self) -> String {
+        format!(
+            "# station {} start {} end {} window {}",
+            self.station_number,
+            self.start.timestamp(),
+            self.end.timestamp(),
+            self.downloader.window_size().num_seconds()
+        )
+    }
+
+    fn window_path(&self, start: i64, end: i64) -> PathBuf {
+        self.directory.join(format!("events_{}_{}.tsv", start, end))
+    }
+}
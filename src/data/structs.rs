@@ -1,10 +1,18 @@
 use std::fmt::Debug;
+use std::io::BufRead;
+use std::ops::Index;
 use std::str::FromStr;
 
-use anyhow::{anyhow, Context, Error, Result};
+use crate::error::{Context, HisparcError, Result};
 use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug)]
+#[cfg(feature = "async")]
+use futures_util::stream::{self, Stream};
+#[cfg(feature = "async")]
+use tokio::io::{AsyncBufRead, AsyncBufReadExt};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Event {
     datetime: NaiveDateTime,
     timestamp: DateTime<Utc>,
@@ -16,7 +24,122 @@ pub struct Event {
     reconstructed_angle: Option<AxialCoord>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WeatherRecord {
+    pub datetime: NaiveDateTime,
+    pub timestamp: DateTime<Utc>,
+    pub temperature_inside: Option<f32>,
+    pub temperature_outside: Option<f32>,
+    pub humidity_inside: Option<f32>,
+    pub humidity_outside: Option<f32>,
+    pub barometer: Option<f32>,
+    pub wind_direction: Option<f32>,
+    pub wind_speed: Option<f32>,
+    pub solar_radiation: Option<f32>,
+    pub uv_index: Option<f32>,
+    pub evapotranspiration: Option<f32>,
+    pub rain_rate: Option<f32>,
+    pub heat_index: Option<f32>,
+    pub dew_point: Option<f32>,
+    pub wind_chill: Option<f32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SinglesRecord {
+    pub datetime: NaiveDateTime,
+    pub timestamp: DateTime<Utc>,
+    pub low: DetectorDataGroup<u32>,
+    pub high: DetectorDataGroup<u32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LightningType {
+    SinglePoint,
+    CloudCloud,
+    CloudCloudMid,
+    CloudCloudEnd,
+    CloudGround,
+    CloudGroundReturn,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LightningStrike {
+    pub datetime: NaiveDateTime,
+    pub timestamp: DateTime<Utc>,
+    pub latitude: f64,
+    pub longitude: f64,
+    pub current: f32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StationSelection {
+    Network,
+    Cluster(String),
+    Stations(Vec<u32>),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Coincidence {
+    pub id: u64,
+    pub events: Vec<CoincidenceEvent>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CoincidenceEvent {
+    pub station_number: u32,
+    pub event: Event,
+}
+
+pub struct EventStream<R: BufRead> {
+    reader: R,
+    buffer: String,
+    parser: EventLineParser,
+}
+
+#[cfg(feature = "async")]
+pub struct AsyncEventStream<R: AsyncBufRead + Unpin> {
+    reader: R,
+    buffer: String,
+    parser: EventLineParser,
+}
+
+// Shared by the blocking and async streams, which only differ in how they
+// read lines.
+#[derive(Debug, Default)]
+pub(crate) struct EventLineParser {
+    line_number: usize,
+    columns: Option<ColumnMap>,
+    metadata: DownloadMetadata,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColumnMap {
+    date: usize,
+    time: usize,
+    timestamp: usize,
+    nanoseconds: Option<usize>,
+    pulseheights: [Option<usize>; 4],
+    integrals: [Option<usize>; 4],
+    mips_numbers: [Option<usize>; 4],
+    arrival_times: [Option<usize>; 4],
+    trigger_time: usize,
+    zenith: Option<usize>,
+    azimuth: Option<usize>,
+    width: usize,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DownloadMetadata {
+    pub station_number: Option<u32>,
+    pub station_name: Option<String>,
+    pub start: Option<NaiveDateTime>,
+    pub end: Option<NaiveDateTime>,
+    pub columns: Vec<String>,
+    pub column_descriptions: Vec<(String, String)>,
+    pub comments: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct DetectorDataGroup<T> {
     detector_1: Option<T>,
     detector_2: Option<T>,
@@ -24,7 +147,7 @@ pub struct DetectorDataGroup<T> {
     detector_4: Option<T>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct AxialCoord {
     zenith: f32,
     azimuth: f32,
@@ -32,6 +155,10 @@ pub struct AxialCoord {
 
 impl Event {
     pub fn from_tsv(input: &str) -> Result<Self> {
+        Self::from_tsv_with_columns(input, &ColumnMap::default())
+    }
+
+    pub fn from_tsv_with_columns(input: &str, columns: &ColumnMap) -> Result<Self> {
         let split: Vec<&str> = input.split('\t').collect();
 
         // -1 if detector not present
@@ -47,71 +174,47 @@ impl Event {
         // Trigger Time NS
         // Zenith
         // Azimuth
+        // See ColumnMap for which of these may be absent.
 
-        let gps_date = NaiveDate::parse_from_str(split[0], "%Y-%m-%d").context(format!(
-            "attempted to parse {} using \"%d-%m-%Y\"",
-            split[0]
-        ))?;
-        let gps_time = NaiveTime::parse_from_str(split[1], "%H:%M:%S").context(format!(
-            "attempted to parse {} using \"%H:%M:%S\"",
-            split[1]
-        ))?;
-        let gps_timestamp = NaiveDateTime::new(gps_date, gps_time);
-
-        let unix_timestamp_s = split[2]
-            .parse::<i64>()
-            .context(format!("attempted to parse {} as i64", split[2]))?;
-        let unix_timestamp_ns = split[3]
-            .parse::<u32>()
-            .context(format!("attempted to parse {} as u32", split[2]))?;
-
-        let unix_timestamp =
-            match NaiveDateTime::from_timestamp_opt(unix_timestamp_s, unix_timestamp_ns) {
-                Some(t) => t,
-                None => {
-                    return Err(anyhow!(
-                        "Time {} {} is too far in the future!",
-                        unix_timestamp_s,
-                        unix_timestamp_ns
-                    ))
-                }
-            }
-            .and_utc();
+        if split.len() < columns.width {
+            return Err(HisparcError::parse(format!(
+                "Event line {:?} has {} columns, expected {}",
+                input,
+                split.len(),
+                columns.width
+            )));
+        }
+
+        let nanoseconds = columns.nanoseconds.map_or("0", |i| split[i]);
 
-        let pulseheights_raw: Vec<Option<u32>> =
-            parse_list(split[4..8].to_vec()).context("parsing pulseheights")?;
-        let integrals_raw: Vec<Option<u32>> =
-            parse_list(split[8..12].to_vec()).context("parsing integrals")?;
-        let mips_numbers_raw: Vec<Option<f32>> =
-            parse_list(split[12..16].to_vec()).context("parsing mips_numbers")?;
-        let arrival_times_raw: Vec<Option<f32>> =
-            parse_list(split[16..20].to_vec()).context("parsing arrival_times")?;
+        let gps_timestamp = parse_date_time(split[columns.date], split[columns.time])?;
+        let unix_timestamp = parse_unix_timestamp(split[columns.timestamp], nanoseconds)?;
 
         let pulseheights =
-            map_list_of_four_to_detector_group(pulseheights_raw).context("mapping pulseheights")?;
+            parse_detector_group(&split, &columns.pulseheights).context("parsing pulseheights")?;
         let integrals =
-            map_list_of_four_to_detector_group(integrals_raw).context("parsing integrals")?;
+            parse_detector_group(&split, &columns.integrals).context("parsing integrals")?;
         let mips_numbers =
-            map_list_of_four_to_detector_group(mips_numbers_raw).context("parsing mips_numbers")?;
-        let arrival_times = map_list_of_four_to_detector_group(arrival_times_raw)
+            parse_detector_group(&split, &columns.mips_numbers).context("parsing mips_numbers")?;
+        let arrival_times = parse_detector_group(&split, &columns.arrival_times)
             .context("parsing arrival_times")?;
 
-        let trigger_time: f32 = split[20]
-            .parse()
-            .context(format!("parsing {} as f32 for trigger time", split[20]))?;
+        let trigger_time_str = split[columns.trigger_time];
+        let trigger_time: f32 = trigger_time_str.parse().context(format!(
+            "parsing {} as f32 for trigger time",
+            trigger_time_str
+        ))?;
 
-        let angles: Vec<Option<f32>> =
-            parse_list(split[21..23].to_vec()).context("parsing angles")?;
+        let angles: Vec<Option<f32>> = parse_list(vec![
+            columns.zenith.map_or("-999", |i| split[i]),
+            columns.azimuth.map_or("-999", |i| split[i]),
+        ])
+        .context("parsing angles")?;
 
-        let angle;
-        if angles[0] == None || angles[1] == None {
-            angle = None;
-        } else {
-            angle = Some(AxialCoord {
-                zenith: angles[0].unwrap(),
-                azimuth: angles[1].unwrap(),
-            });
-        }
+        let angle = match (angles[0], angles[1]) {
+            (Some(zenith), Some(azimuth)) => Some(AxialCoord { zenith, azimuth }),
+            _ => None,
+        };
 
         Ok(Self {
             datetime: gps_timestamp,
@@ -126,6 +229,788 @@ impl Event {
     }
 }
 
+impl Event {
+    pub fn datetime(&self) -> NaiveDateTime {
+        self.datetime
+    }
+
+    pub fn timestamp(&self) -> DateTime<Utc> {
+        self.timestamp
+    }
+
+    // Seconds and nanoseconds since the unix epoch combined into one number,
+    // as used by the API to identify an event (e.g. for get_event_trace).
+    pub fn ext_timestamp(&self) -> u64 {
+        self.timestamp.timestamp() as u64 * 1_000_000_000
+            + self.timestamp.timestamp_subsec_nanos() as u64
+    }
+
+    pub fn pulseheights(&self) -> &DetectorDataGroup<u32> {
+        &self.pulseheights
+    }
+
+    pub fn integrals(&self) -> &DetectorDataGroup<u32> {
+        &self.integrals
+    }
+
+    pub fn mips_numbers(&self) -> &DetectorDataGroup<f32> {
+        &self.mips_numbers
+    }
+
+    pub fn arrival_times(&self) -> &DetectorDataGroup<f32> {
+        &self.arrival_times
+    }
+
+    pub fn trigger_time(&self) -> f32 {
+        self.trigger_time
+    }
+
+    pub fn reconstructed_angle(&self) -> Option<&AxialCoord> {
+        self.reconstructed_angle.as_ref()
+    }
+
+    pub fn number_of_detectors(&self) -> usize {
+        self.pulseheights.number_of_detectors()
+    }
+}
+
+impl<T> DetectorDataGroup<T> {
+    // Detectors are numbered from 1, as on the station itself.
+    pub fn get(&self, detector: usize) -> Option<&T> {
+        match detector {
+            1 => self.detector_1.as_ref(),
+            2 => self.detector_2.as_ref(),
+            3 => self.detector_3.as_ref(),
+            4 => self.detector_4.as_ref(),
+            _ => None,
+        }
+    }
+
+    pub fn detector_1(&self) -> Option<&T> {
+        self.detector_1.as_ref()
+    }
+
+    pub fn detector_2(&self) -> Option<&T> {
+        self.detector_2.as_ref()
+    }
+
+    pub fn detector_3(&self) -> Option<&T> {
+        self.detector_3.as_ref()
+    }
+
+    pub fn detector_4(&self) -> Option<&T> {
+        self.detector_4.as_ref()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Option<&T>> {
+        [
+            self.detector_1.as_ref(),
+            self.detector_2.as_ref(),
+            self.detector_3.as_ref(),
+            self.detector_4.as_ref(),
+        ]
+        .into_iter()
+    }
+
+    pub fn iter_present(&self) -> impl Iterator<Item = (usize, &T)> {
+        self.iter()
+            .enumerate()
+            .filter_map(|(i, x)| x.map(|x| (i + 1, x)))
+    }
+
+    pub fn number_of_detectors(&self) -> usize {
+        self.iter().flatten().count()
+    }
+}
+
+impl<T> Index<usize> for DetectorDataGroup<T> {
+    type Output = Option<T>;
+
+    fn index(&self, detector: usize) -> &Self::Output {
+        match detector {
+            1 => &self.detector_1,
+            2 => &self.detector_2,
+            3 => &self.detector_3,
+            4 => &self.detector_4,
+            n => panic!(
+                "Detector {} does not exist, detectors are numbered 1 to 4",
+                n
+            ),
+        }
+    }
+}
+
+impl AxialCoord {
+    pub fn zenith(&self) -> f32 {
+        self.zenith
+    }
+
+    pub fn azimuth(&self) -> f32 {
+        self.azimuth
+    }
+}
+
+impl Default for ColumnMap {
+    fn default() -> Self {
+        Self::with_detectors(4, true)
+    }
+}
+
+impl ColumnMap {
+    // Layouts without a header are recognised by their number of columns:
+    // - 23: four detectors with reconstructed angles (current layout)
+    // - 21: four detectors without reconstructed angles
+    // - 15: two detectors with reconstructed angles
+    // - 13: two detectors without reconstructed angles
+    pub fn from_column_count(count: usize) -> Result<Self> {
+        match count {
+            23 => Ok(Self::with_detectors(4, true)),
+            21 => Ok(Self::with_detectors(4, false)),
+            15 => Ok(Self::with_detectors(2, true)),
+            13 => Ok(Self::with_detectors(2, false)),
+            n => Err(HisparcError::parse(format!(
+                "Unknown event layout with {} columns",
+                n
+            ))),
+        }
+    }
+
+    pub fn from_header<S: AsRef<str>>(columns: &[S]) -> Result<Self> {
+        let mut date = None;
+        let mut time = None;
+        let mut timestamp = None;
+        let mut trigger_time = None;
+
+        let mut map = Self {
+            date: 0,
+            time: 0,
+            timestamp: 0,
+            nanoseconds: None,
+            pulseheights: [None; 4],
+            integrals: [None; 4],
+            mips_numbers: [None; 4],
+            arrival_times: [None; 4],
+            trigger_time: 0,
+            zenith: None,
+            azimuth: None,
+            width: columns.len(),
+        };
+
+        for (i, column) in columns.iter().enumerate() {
+            let name = column.as_ref().trim().to_lowercase();
+
+            match name.as_str() {
+                "date" => date = Some(i),
+                "time" => time = Some(i),
+                "timestamp" => timestamp = Some(i),
+                "nanoseconds" | "ns" | "timestamp_ns" => map.nanoseconds = Some(i),
+                "t_trigger" | "trigger_time" => trigger_time = Some(i),
+                "zenith" => map.zenith = Some(i),
+                "azimuth" => map.azimuth = Some(i),
+                _ => {
+                    let base = name.trim_end_matches(|c: char| c.is_ascii_digit());
+                    let detector = match name[base.len()..].parse::<usize>() {
+                        Ok(n @ 1..=4) => n - 1,
+                        _ => continue,
+                    };
+
+                    let group = match base.trim_end_matches('_') {
+                        "pulseheights" | "pulseheight" | "ph" => &mut map.pulseheights,
+                        "integrals" | "integral" | "int" => &mut map.integrals,
+                        "n" | "mips" | "n_mips" => &mut map.mips_numbers,
+                        "t" | "arrival_times" | "arrival_time" => &mut map.arrival_times,
+                        _ => continue,
+                    };
+                    group[detector] = Some(i);
+                }
+            }
+        }
+
+        match (date, time, timestamp, trigger_time) {
+            (Some(date), Some(time), Some(timestamp), Some(trigger_time)) => {
+                map.date = date;
+                map.time = time;
+                map.timestamp = timestamp;
+                map.trigger_time = trigger_time;
+                Ok(map)
+            }
+            _ => Err(HisparcError::parse(format!(
+                "Header {:?} is missing one of date, time, timestamp or t_trigger",
+                columns.iter().map(|x| x.as_ref()).collect::<Vec<&str>>()
+            ))),
+        }
+    }
+
+    pub fn number_of_detectors(&self) -> usize {
+        self.pulseheights.iter().filter(|x| x.is_some()).count()
+    }
+
+    fn with_detectors(detectors: usize, angles: bool) -> Self {
+        let group = |first: usize| {
+            let mut indices = [None; 4];
+            for (i, index) in indices.iter_mut().take(detectors).enumerate() {
+                *index = Some(first + i);
+            }
+            indices
+        };
+
+        let trigger_time = 4 + 4 * detectors;
+
+        Self {
+            date: 0,
+            time: 1,
+            timestamp: 2,
+            nanoseconds: Some(3),
+            pulseheights: group(4),
+            integrals: group(4 + detectors),
+            mips_numbers: group(4 + 2 * detectors),
+            arrival_times: group(4 + 3 * detectors),
+            trigger_time,
+            zenith: angles.then_some(trigger_time + 1),
+            azimuth: angles.then_some(trigger_time + 2),
+            width: if angles {
+                trigger_time + 3
+            } else {
+                trigger_time + 1
+            },
+        }
+    }
+}
+
+impl WeatherRecord {
+    pub fn from_tsv(input: &str) -> Result<Self> {
+        let split: Vec<&str> = input.split('\t').collect();
+
+        // -1 or -999 if the sensor is missing or the reading is invalid
+        // Date
+        // Time
+        // Unix Timestamp
+        // Temperature inside / outside (C)
+        // Humidity inside / outside (%)
+        // Barometer (hPa)
+        // Wind direction (degrees)
+        // Wind speed (m/s)
+        // Solar radiation (W/m2)
+        // UV index
+        // Evapotranspiration (mm)
+        // Rain rate (mm/h)
+        // Heat index (C)
+        // Dew point (C)
+        // Wind chill (C)
+
+        if split.len() != 17 {
+            return Err(HisparcError::parse(format!(
+                "Weather line {:?} has {} columns, expected 17",
+                input,
+                split.len()
+            )));
+        }
+
+        let datetime = parse_date_time(split[0], split[1])?;
+        let timestamp = parse_unix_timestamp(split[2], "0")?;
+
+        let values: Vec<Option<f32>> =
+            parse_list(split[3..17].to_vec()).context("parsing weather values")?;
+
+        Ok(Self {
+            datetime,
+            timestamp,
+            temperature_inside: values[0],
+            temperature_outside: values[1],
+            humidity_inside: values[2],
+            humidity_outside: values[3],
+            barometer: values[4],
+            wind_direction: values[5],
+            wind_speed: values[6],
+            solar_radiation: values[7],
+            uv_index: values[8],
+            evapotranspiration: values[9],
+            rain_rate: values[10],
+            heat_index: values[11],
+            dew_point: values[12],
+            wind_chill: values[13],
+        })
+    }
+}
+
+impl SinglesRecord {
+    pub fn from_tsv(input: &str) -> Result<Self> {
+        let split: Vec<&str> = input.split('\t').collect();
+
+        // -1 if detector not present
+        // Date
+        // Time
+        // Unix Timestamp
+        // (Low, High) singles rate (counts/s) x4, interleaved per detector
+
+        if split.len() != 11 {
+            return Err(HisparcError::parse(format!(
+                "Singles line {:?} has {} columns, expected 11",
+                input,
+                split.len()
+            )));
+        }
+
+        let datetime = parse_date_time(split[0], split[1])?;
+        let timestamp = parse_unix_timestamp(split[2], "0")?;
+
+        let rates: Vec<Option<u32>> =
+            parse_list(split[3..11].to_vec()).context("parsing singles rates")?;
+
+        let low_raw: Vec<Option<u32>> = rates.iter().step_by(2).cloned().collect();
+        let high_raw: Vec<Option<u32>> = rates.iter().skip(1).step_by(2).cloned().collect();
+
+        let low = map_list_of_four_to_detector_group(low_raw).context("mapping low singles")?;
+        let high = map_list_of_four_to_detector_group(high_raw).context("mapping high singles")?;
+
+        Ok(Self {
+            datetime,
+            timestamp,
+            low,
+            high,
+        })
+    }
+}
+
+impl LightningType {
+    pub fn code(&self) -> u32 {
+        match self {
+            LightningType::SinglePoint => 0,
+            LightningType::CloudCloud => 1,
+            LightningType::CloudCloudMid => 2,
+            LightningType::CloudCloudEnd => 3,
+            LightningType::CloudGround => 4,
+            LightningType::CloudGroundReturn => 5,
+        }
+    }
+}
+
+impl LightningStrike {
+    pub fn from_tsv(input: &str) -> Result<Self> {
+        let split: Vec<&str> = input.split('\t').collect();
+
+        // Date
+        // Time
+        // Unix Timestamp
+        // Unix Timestamp NS
+        // Latitude (degrees)
+        // Longitude (degrees)
+        // Current (kA), negative for negative strikes
+
+        if split.len() != 7 {
+            return Err(HisparcError::parse(format!(
+                "Lightning line {:?} has {} columns, expected 7",
+                input,
+                split.len()
+            )));
+        }
+
+        let datetime = parse_date_time(split[0], split[1])?;
+        let timestamp = parse_unix_timestamp(split[2], split[3])?;
+
+        let latitude: f64 = split[4]
+            .parse()
+            .context(format!("parsing {} as f64 for latitude", split[4]))?;
+        let longitude: f64 = split[5]
+            .parse()
+            .context(format!("parsing {} as f64 for longitude", split[5]))?;
+        let current: f32 = split[6]
+            .parse()
+            .context(format!("parsing {} as f32 for current", split[6]))?;
+
+        Ok(Self {
+            datetime,
+            timestamp,
+            latitude,
+            longitude,
+            current,
+        })
+    }
+}
+
+impl Coincidence {
+    // Comment and empty lines are skipped. Errors carry the line number,
+    // counting every line given.
+    pub fn from_tsv_lines<'a, I: IntoIterator<Item = &'a str>>(lines: I) -> Result<Vec<Self>> {
+        let mut coincidences: Vec<Coincidence> = Vec::new();
+
+        for (i, line) in lines.into_iter().enumerate() {
+            if line.starts_with('#') || line.is_empty() {
+                continue;
+            }
+
+            let (id, coincidence_event) = Self::parse_line(line).map_err(|e| e.at_line(i + 1))?;
+
+            match coincidences.last_mut() {
+                Some(c) if c.id == id => c.events.push(coincidence_event),
+                _ => coincidences.push(Coincidence {
+                    id,
+                    events: vec![coincidence_event],
+                }),
+            }
+        }
+
+        Ok(coincidences)
+    }
+
+    fn parse_line(line: &str) -> Result<(u64, CoincidenceEvent)> {
+        // Coincidence ID
+        // Station Number
+        // Event columns, as in Event::from_tsv
+        let split: Vec<&str> = line.splitn(3, '\t').collect();
+
+        if split.len() != 3 {
+            return Err(HisparcError::parse(format!(
+                "Coincidence line {:?} has too few columns",
+                line
+            )));
+        }
+
+        let id: u64 = split[0]
+            .parse()
+            .context(format!("parsing {} as u64 for coincidence id", split[0]))?;
+        let station_number: u32 = split[1]
+            .parse()
+            .context(format!("parsing {} as u32 for station number", split[1]))?;
+        let event = Event::from_tsv(split[2]).context(format!(
+            "parsing event of station {} in coincidence {}",
+            station_number, id
+        ))?;
+
+        Ok((
+            id,
+            CoincidenceEvent {
+                station_number,
+                event,
+            },
+        ))
+    }
+}
+
+impl<R: BufRead> EventStream<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            buffer: String::new(),
+            parser: EventLineParser::default(),
+        }
+    }
+
+    pub fn with_columns(reader: R, columns: ColumnMap) -> Self {
+        Self {
+            parser: EventLineParser::with_columns(columns),
+            ..Self::new(reader)
+        }
+    }
+
+    pub fn columns(&self) -> Option<&ColumnMap> {
+        self.parser.columns.as_ref()
+    }
+
+    // Only complete once the first event has been read, as the header
+    // precedes the data.
+    pub fn metadata(&self) -> &DownloadMetadata {
+        &self.parser.metadata
+    }
+
+    pub fn into_metadata(self) -> DownloadMetadata {
+        self.parser.metadata
+    }
+}
+
+impl<R: BufRead> Iterator for EventStream<R> {
+    type Item = Result<Event>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            self.buffer.clear();
+
+            match self.reader.read_line(&mut self.buffer) {
+                Ok(0) => return None,
+                Ok(_) => {}
+                Err(e) => return Some(Err(self.parser.read_error(e))),
+            }
+
+            if let Some(event) = self.parser.parse_line(&self.buffer) {
+                return Some(event);
+            }
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+impl<R: AsyncBufRead + Unpin> AsyncEventStream<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            buffer: String::new(),
+            parser: EventLineParser::default(),
+        }
+    }
+
+    pub fn with_columns(reader: R, columns: ColumnMap) -> Self {
+        Self {
+            parser: EventLineParser::with_columns(columns),
+            ..Self::new(reader)
+        }
+    }
+
+    pub fn columns(&self) -> Option<&ColumnMap> {
+        self.parser.columns.as_ref()
+    }
+
+    // Only complete once the first event has been read, as the header
+    // precedes the data.
+    pub fn metadata(&self) -> &DownloadMetadata {
+        &self.parser.metadata
+    }
+
+    pub fn into_metadata(self) -> DownloadMetadata {
+        self.parser.metadata
+    }
+
+    pub async fn next_event(&mut self) -> Option<Result<Event>> {
+        loop {
+            self.buffer.clear();
+
+            match self.reader.read_line(&mut self.buffer).await {
+                Ok(0) => return None,
+                Ok(_) => {}
+                Err(e) => return Some(Err(self.parser.read_error(e))),
+            }
+
+            if let Some(event) = self.parser.parse_line(&self.buffer) {
+                return Some(event);
+            }
+        }
+    }
+
+    pub async fn collect_events(&mut self) -> Result<Vec<Event>> {
+        let mut events = Vec::new();
+
+        while let Some(event) = self.next_event().await {
+            events.push(event?);
+        }
+
+        Ok(events)
+    }
+
+    pub fn into_stream(self) -> impl Stream<Item = Result<Event>> {
+        stream::unfold(self, |mut events| async move {
+            events.next_event().await.map(|event| (event, events))
+        })
+    }
+}
+
+impl EventLineParser {
+    fn with_columns(columns: ColumnMap) -> Self {
+        Self {
+            columns: Some(columns),
+            ..Self::default()
+        }
+    }
+
+    fn read_error(&self, e: std::io::Error) -> HisparcError {
+        HisparcError::Io(e).at_line(self.line_number + 1)
+    }
+
+    // Returns None for lines that do not hold an event
+    fn parse_line(&mut self, line: &str) -> Option<Result<Event>> {
+        self.line_number += 1;
+
+        let line = line.trim_end_matches(['\r', '\n']);
+
+        if line.starts_with('#') {
+            self.metadata.parse_comment(line);
+            return None;
+        }
+
+        if line.is_empty() {
+            return None;
+        }
+
+        // Some older files carry an uncommented header row. Anything else
+        // that is not an event, such as an HTML error page, is an error.
+        if self.columns.is_none() && !line.starts_with(|c: char| c.is_ascii_digit()) {
+            let header: Vec<String> = line.split('\t').map(|x| x.trim().to_string()).collect();
+
+            return match ColumnMap::from_header(&header) {
+                Ok(columns) => {
+                    self.columns = Some(columns);
+                    self.metadata.columns = header;
+                    None
+                }
+                Err(e) => {
+                    let error = HisparcError::Context {
+                        context: format!("expected an event or a header, found {:?}", line),
+                        source: Box::new(e),
+                    };
+                    Some(Err(error.at_line(self.line_number)))
+                }
+            };
+        }
+
+        let columns = match &self.columns {
+            Some(columns) => columns,
+            None => {
+                let columns = ColumnMap::from_header(&self.metadata.columns)
+                    .or_else(|_| ColumnMap::from_column_count(line.split('\t').count()));
+
+                match columns {
+                    Ok(columns) => self.columns.insert(columns),
+                    Err(e) => return Some(Err(e.at_line(self.line_number))),
+                }
+            }
+        };
+
+        Some(Event::from_tsv_with_columns(line, columns).map_err(|e| e.at_line(self.line_number)))
+    }
+}
+
+impl DownloadMetadata {
+    pub fn from_tsv_header<'a, I: IntoIterator<Item = &'a str>>(lines: I) -> Self {
+        let mut metadata = Self::default();
+
+        for line in lines.into_iter().take_while(|x| x.starts_with('#')) {
+            metadata.parse_comment(line);
+        }
+
+        metadata
+    }
+
+    // Header lines look like:
+    // # Station: (501) Nikhef
+    // # Data from: 2023-05-17 00:00:00 to 2023-05-18 00:00:00
+    // #    pulseheights1: pulse height of detector 1 (ADC)
+    // # date\ttime\ttimestamp\tnanoseconds\t...
+    pub fn parse_comment(&mut self, line: &str) {
+        let content = line.trim_start_matches('#').trim();
+        self.comments.push(content.to_string());
+
+        if content.is_empty() {
+            return;
+        }
+
+        if content.contains('\t') && !content.contains(':') {
+            self.columns = content.split('\t').map(|x| x.trim().to_string()).collect();
+            return;
+        }
+
+        let lower = content.to_lowercase();
+
+        if lower.starts_with("station") {
+            if self.station_number.is_none() {
+                self.parse_station(content["station".len()..].trim_start_matches([':', ' ']));
+            }
+            return;
+        }
+
+        if lower.contains("from") && (lower.contains(" to ") || lower.contains(" until ")) {
+            if let Some((start, end)) = parse_period(content) {
+                self.start = Some(start);
+                self.end = Some(end);
+                return;
+            }
+        }
+
+        if let Some((name, description)) = content.split_once(':') {
+            let name = name.trim();
+            if !name.is_empty() && !name.contains(' ') {
+                self.column_descriptions
+                    .push((name.to_string(), description.trim().to_string()));
+            }
+        }
+    }
+
+    pub fn has_column(&self, name: &str) -> bool {
+        self.columns.iter().any(|x| x == name)
+            || self.column_descriptions.iter().any(|(x, _)| x == name)
+    }
+
+    fn parse_station(&mut self, text: &str) {
+        let digits: String = text
+            .chars()
+            .skip_while(|c| !c.is_ascii_digit())
+            .take_while(|c| c.is_ascii_digit())
+            .collect();
+
+        self.station_number = digits.parse().ok();
+
+        let name = match text.split_once(')') {
+            Some((_, name)) => name,
+            None => text.trim_start_matches(|c: char| c.is_ascii_digit()),
+        }
+        .trim();
+
+        if !name.is_empty() {
+            self.station_name = Some(name.to_string());
+        }
+    }
+}
+
+fn parse_period(text: &str) -> Option<(NaiveDateTime, NaiveDateTime)> {
+    let (_, period) = text.split_once("from")?;
+    let period = period.trim_start_matches([':', ' ']);
+
+    let (start, end) = period
+        .split_once(" to ")
+        .or_else(|| period.split_once(" until "))?;
+
+    Some((parse_header_date(start)?, parse_header_date(end)?))
+}
+
+fn parse_header_date(text: &str) -> Option<NaiveDateTime> {
+    let text = text.trim().trim_end_matches('.');
+
+    NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M:%S")
+        .ok()
+        .or_else(|| {
+            NaiveDate::parse_from_str(text, "%Y-%m-%d")
+                .ok()
+                .and_then(|x| x.and_hms_opt(0, 0, 0))
+        })
+}
+
+fn parse_date_time(date: &str, time: &str) -> Result<NaiveDateTime> {
+    let parsed_date = NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .context(format!("attempted to parse {} using \"%Y-%m-%d\"", date))?;
+    let parsed_time = NaiveTime::parse_from_str(time, "%H:%M:%S")
+        .context(format!("attempted to parse {} using \"%H:%M:%S\"", time))?;
+    Ok(NaiveDateTime::new(parsed_date, parsed_time))
+}
+
+fn parse_unix_timestamp(seconds: &str, nanoseconds: &str) -> Result<DateTime<Utc>> {
+    let unix_timestamp_s = seconds
+        .parse::<i64>()
+        .context(format!("attempted to parse {} as i64", seconds))?;
+    let unix_timestamp_ns = nanoseconds
+        .parse::<u32>()
+        .context(format!("attempted to parse {} as u32", nanoseconds))?;
+
+    match NaiveDateTime::from_timestamp_opt(unix_timestamp_s, unix_timestamp_ns) {
+        Some(t) => Ok(t.and_utc()),
+        None => Err(HisparcError::parse(format!(
+            "Time {} {} is too far in the future!",
+            unix_timestamp_s, unix_timestamp_ns
+        ))),
+    }
+}
+
+fn parse_detector_group<T: FromStr + Clone + Debug>(
+    split: &[&str],
+    indices: &[Option<usize>; 4],
+) -> Result<DetectorDataGroup<T>>
+where
+    <T as FromStr>::Err: Send + Sync + std::error::Error + 'static,
+{
+    let raw: Vec<&str> = indices
+        .iter()
+        .map(|x| x.map_or("-1", |i| split[i]))
+        .collect();
+
+    map_list_of_four_to_detector_group(parse_list(raw)?)
+}
+
 fn parse_list<T: FromStr>(input_vec: Vec<&str>) -> Result<Vec<Option<T>>>
 where
     <T as FromStr>::Err: Send + Sync + std::error::Error + 'static,
@@ -145,8 +1030,7 @@ where
                         result.push(Some(e));
                     }
                     Err(e) => {
-                        let err: Error = e.into();
-                        return Err(err).context(format!(
+                        return Err(HisparcError::parse(e)).context(format!(
                             "attempted to parse {} from vec {:?}",
                             item, input_vec
                         ));
@@ -163,7 +1047,10 @@ fn map_list_of_four_to_detector_group<T: Clone + Debug>(
     input_vec: Vec<Option<T>>,
 ) -> Result<DetectorDataGroup<T>> {
     if input_vec.len() != 4 {
-        Err(anyhow!("Input vector {:?} is not of length 4!", input_vec))
+        Err(HisparcError::parse(format!(
+            "Input vector {:?} is not of length 4!",
+            input_vec
+        )))
     } else {
         Ok(DetectorDataGroup {
             detector_1: input_vec[0].clone(),
@@ -0,0 +1,179 @@
+use reqwest::StatusCode;
+use std::fmt::Display;
+use thiserror::Error;
+
+pub type Result<T, E = HisparcError> = std::result::Result<T, E>;
+
+#[derive(Debug, Error)]
+pub enum HisparcError {
+    // The server has nothing at this URL, e.g. a station without data
+    #[error("nothing found at {url}")]
+    NotFound { url: String },
+
+    #[error("request to {url} timed out")]
+    Timeout {
+        url: String,
+        #[source]
+        source: reqwest::Error,
+    },
+
+    #[error("could not reach {url}")]
+    Network {
+        url: String,
+        #[source]
+        source: reqwest::Error,
+    },
+
+    #[error("server responded with {status} for {url}")]
+    Status { url: String, status: u16 },
+
+    // The response was not the JSON shape we expected. The source is a
+    // reqwest::Error for live responses and a serde_json::Error for cached
+    // and recorded ones.
+    #[error("unexpected response from {url}")]
+    Json {
+        url: String,
+        #[source]
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+
+    #[error("malformed data on line {line}")]
+    Tsv {
+        line: usize,
+        #[source]
+        source: Box<HisparcError>,
+    },
+
+    // Replaying a cassette that has no response for this URL
+    #[error("no recorded response for {url}")]
+    NotRecorded { url: String },
+
+    #[error("{0}")]
+    Parse(String),
+
+    #[error("{0}")]
+    InvalidInput(String),
+
+    // Building the underlying HTTP client failed, e.g. on a bad proxy
+    #[error("could not set up the HTTP client")]
+    Client(#[source] reqwest::Error),
+
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
+    #[error("{context}")]
+    Context {
+        context: String,
+        #[source]
+        source: Box<HisparcError>,
+    },
+}
+
+impl HisparcError {
+    pub(crate) fn from_reqwest(error: reqwest::Error, url: &str) -> Self {
+        let url = match error.url() {
+            Some(url) => url.to_string(),
+            None => url.to_string(),
+        };
+
+        if let Some(status) = error.status() {
+            if status == StatusCode::NOT_FOUND {
+                HisparcError::NotFound { url }
+            } else {
+                HisparcError::Status {
+                    url,
+                    status: status.as_u16(),
+                }
+            }
+        } else if error.is_timeout() {
+            HisparcError::Timeout { url, source: error }
+        } else if error.is_decode() {
+            HisparcError::Json {
+                url,
+                source: Box::new(error),
+            }
+        } else {
+            HisparcError::Network { url, source: error }
+        }
+    }
+
+    pub(crate) fn parse<S: Display>(message: S) -> Self {
+        HisparcError::Parse(message.to_string())
+    }
+
+    pub(crate) fn at_line(self, line: usize) -> Self {
+        HisparcError::Tsv {
+            line,
+            source: Box::new(self),
+        }
+    }
+
+    // The error underneath any context and line information
+    pub fn root(&self) -> &HisparcError {
+        match self {
+            HisparcError::Context { source, .. } | HisparcError::Tsv { source, .. } => {
+                source.root()
+            }
+            error => error,
+        }
+    }
+
+    // The first line number attached to this error, if any
+    pub fn line(&self) -> Option<usize> {
+        match self {
+            HisparcError::Tsv { line, .. } => Some(*line),
+            HisparcError::Context { source, .. } => source.line(),
+            _ => None,
+        }
+    }
+
+    pub fn is_not_found(&self) -> bool {
+        matches!(self.root(), HisparcError::NotFound { .. })
+    }
+
+    pub fn is_timeout(&self) -> bool {
+        matches!(self.root(), HisparcError::Timeout { .. })
+    }
+
+    // Timeouts, refused connections and 5xx responses, i.e. failures that say
+    // nothing about the data itself
+    pub fn is_server_unavailable(&self) -> bool {
+        match self.root() {
+            HisparcError::Timeout { .. } | HisparcError::Network { .. } => true,
+            HisparcError::Status { status, .. } => *status >= 500,
+            _ => false,
+        }
+    }
+}
+
+impl From<std::num::ParseIntError> for HisparcError {
+    fn from(error: std::num::ParseIntError) -> Self {
+        HisparcError::parse(error)
+    }
+}
+
+impl From<std::num::ParseFloatError> for HisparcError {
+    fn from(error: std::num::ParseFloatError) -> Self {
+        HisparcError::parse(error)
+    }
+}
+
+impl From<chrono::ParseError> for HisparcError {
+    fn from(error: chrono::ParseError) -> Self {
+        HisparcError::parse(error)
+    }
+}
+
+// Mirrors anyhow's Context, wrapping the error instead of erasing its type
+pub(crate) trait Context<T> {
+    fn context<C: Display>(self, context: C) -> Result<T>;
+}
+
+impl<T, E: Into<HisparcError>> Context<T> for std::result::Result<T, E> {
+    fn context<C: Display>(self, context: C) -> Result<T> {
+        self.map_err(|error| HisparcError::Context {
+            context: context.to_string(),
+            source: Box::new(error.into()),
+        })
+    }
+}
@@ -1,2 +1,19 @@
 pub mod api;
+#[cfg(feature = "async")]
+pub mod async_client;
+pub mod cache;
+pub mod cassette;
+pub mod client;
+mod concurrent;
 pub mod data;
+pub mod error;
+#[cfg(feature = "mock")]
+pub mod mock;
+pub mod retry;
+#[cfg(feature = "async")]
+pub use async_client::AsyncHisparcClient;
+pub use cache::{CacheEntry, ResponseCache};
+pub use cassette::{Cassette, CassetteMode};
+pub use client::{HisparcClient, HisparcClientBuilder};
+pub use error::{HisparcError, Result};
+pub use retry::{RateLimiter, RetryPolicy};
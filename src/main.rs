@@ -1,34 +1,36 @@
 use anyhow::{Context, Result};
-use chrono::prelude::NaiveDateTime;
+use chrono::prelude::{NaiveDate, NaiveDateTime};
 use hisparc::api::*;
 use hisparc::data::*;
 
 fn main() -> Result<()> {
-    let station_info = get_station_info(4, 2023, 5, 23)?;
+    let date = NaiveDate::from_ymd_opt(2023, 5, 23).context("Date")?;
+
+    let station_info = get_station_info(4, date)?;
     println!("{:#?}", station_info);
 
-    // let stations_with_data = get_stations_with_data(2023, 5, 23)?;
+    // let stations_with_data = get_stations_with_data(date)?;
     // println!("{:#?}", stations_with_data);
 
-    // let has_singles = get_has_singles(14006, 2023, 5, 23)?;
+    // let has_singles = get_has_singles(14006, date)?;
     // println!("{:#?}", has_singles);
 
     // let subclusters_in_cluster = get_subclusters_in_cluster(1000)?;
     // println!("{:#?}", subclusters_in_cluster);
 
-    // let config = get_configuration(14006, 2023, 5, 23)?;
+    // let config = get_configuration(14006, date)?;
     // println!("{:#?}", config);
 
     // let clusters = get_clusters()?;
     // println!("{:#?}", clusters);
 
-    // let num_events = get_number_of_events(14006, 2023, 5, 23, 0)?;
+    // let num_events = get_number_of_events(14006, date.and_hms_opt(0, 0, 0).context("Hour")?)?;
     // println!("{:#?}", num_events);
 
-    // let has_weather = get_has_weather(14006, 2023, 5, 23)?;
+    // let has_weather = get_has_weather(14006, date)?;
     // println!("{:#?}", has_weather);
 
-    // let has_data = get_has_data(14006, 2023, 5, 23)?;
+    // let has_data = get_has_data(14006, date)?;
     // println!("{:#?}", has_data);
 
     // let clusters_in_country = get_clusters_in_country(10000)?;
@@ -46,7 +48,7 @@ fn main() -> Result<()> {
     // let countries = get_countries()?;
     // println!("{:#?}", countries);
 
-    // let stations_with_weather = get_stations_with_weather(2023, 5, 23)?;
+    // let stations_with_weather = get_stations_with_weather(date)?;
     // println!("{:#?}", stations_with_weather[0]);
 
     // let subclusters = get_subclusters()?;
@@ -0,0 +1,316 @@
+use crate::client::{HisparcClient, HisparcClientBuilder};
+use crate::error::Result;
+use crate::retry::RetryPolicy;
+use chrono::NaiveDateTime;
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+
+// A minimal stand-in for data.hisparc.nl that serves responses from a fixture
+// directory, for testing without network access. Layout of the directory:
+//
+// - api.json: the API root, listing the endpoint templates
+// - api/<path>.json: the response for `/api/<path>/`, e.g.
+//   api/station/501/2023/5/17.json for station_info of 501 on 2023-5-17
+// - data/download/<data_type>/<station>.tsv: downloads of events, weather and
+//   singles, e.g. data/download/events/501.tsv
+// - data/download/lightning/<lightning_type>.tsv: lightning downloads
+// - data/network/coincidences.tsv: coincidence downloads
+//
+// Downloads only return the data lines whose timestamp falls within the
+// requested start and end, and keep all header lines. Anything without a
+// fixture is a 404, and paths set with fail answer with that status instead.
+pub struct MockServer {
+    address: SocketAddr,
+    requests: Arc<Mutex<Vec<String>>>,
+    failures: Arc<Mutex<HashMap<String, u16>>>,
+    shutdown: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl MockServer {
+    // Serves on a free port on localhost
+    pub fn start<P: Into<PathBuf>>(fixtures: P) -> io::Result<Self> {
+        Self::bind("127.0.0.1:0", fixtures)
+    }
+
+    pub fn bind<A: ToSocketAddrs, P: Into<PathBuf>>(address: A, fixtures: P) -> io::Result<Self> {
+        let listener = TcpListener::bind(address)?;
+        let address = listener.local_addr()?;
+        let fixtures = fixtures.into();
+
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let failures = Arc::new(Mutex::new(HashMap::new()));
+        let shutdown = Arc::new(AtomicBool::new(false));
+
+        let handle = {
+            let requests = requests.clone();
+            let failures = failures.clone();
+            let shutdown = shutdown.clone();
+
+            thread::spawn(move || {
+                for stream in listener.incoming() {
+                    if shutdown.load(Ordering::SeqCst) {
+                        break;
+                    }
+                    if let Ok(stream) = stream {
+                        // A broken connection only affects that request
+                        let _ = handle_connection(stream, &fixtures, &requests, &failures);
+                    }
+                }
+            })
+        };
+
+        Ok(Self {
+            address,
+            requests,
+            failures,
+            shutdown,
+            handle: Some(handle),
+        })
+    }
+
+    pub fn address(&self) -> SocketAddr {
+        self.address
+    }
+
+    pub fn url(&self) -> String {
+        format!("http://{}/", self.address)
+    }
+
+    pub fn api_url(&self) -> String {
+        format!("http://{}/api/", self.address)
+    }
+
+    pub fn data_url(&self) -> String {
+        format!("http://{}/data/", self.address)
+    }
+
+    // A builder pointed at this server, without retries so that missing
+    // fixtures fail fast
+    pub fn client_builder(&self) -> HisparcClientBuilder {
+        HisparcClient::builder()
+            .api_url(self.api_url())
+            .data_url(self.data_url())
+            .retry_policy(RetryPolicy::never())
+    }
+
+    pub fn client(&self) -> Result<HisparcClient> {
+        self.client_builder().build()
+    }
+
+    // The path and query of every request served so far
+    pub fn requests(&self) -> Vec<String> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    // Answers every request for path, such as /api/, with status until
+    // recover is called, to test how clients handle a failing server
+    pub fn fail(&self, path: &str, status: u16) {
+        self.failures
+            .lock()
+            .unwrap()
+            .insert(path.to_string(), status);
+    }
+
+    pub fn recover(&self, path: &str) {
+        self.failures.lock().unwrap().remove(path);
+    }
+
+    // Blocks until the server is shut down from another thread, for use
+    // from a binary
+    pub fn wait(mut self) {
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        if let Some(handle) = self.handle.take() {
+            self.shutdown.store(true, Ordering::SeqCst);
+            // Wake up the accept loop so it sees the shutdown flag
+            let _ = TcpStream::connect(self.address);
+            let _ = handle.join();
+        }
+    }
+}
+
+fn handle_connection(
+    mut stream: TcpStream,
+    fixtures: &Path,
+    requests: &Mutex<Vec<String>>,
+    failures: &Mutex<HashMap<String, u16>>,
+) -> io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+
+    // Skip the headers, GET requests have no body
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 || header.trim().is_empty() {
+            break;
+        }
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default();
+    let target = parts.next().unwrap_or_default().to_string();
+
+    requests.lock().unwrap().push(target.clone());
+
+    let path = target.split('?').next().unwrap_or_default();
+    if let Some(status) = failures.lock().unwrap().get(path) {
+        let status = format!("{} Mock Failure", status);
+        return write_response(&mut stream, &status, "text/plain", b"Mock Failure");
+    }
+
+    let response = if method == "GET" {
+        respond(fixtures, &target)
+    } else {
+        None
+    };
+
+    match response {
+        Some((content_type, body)) => write_response(&mut stream, "200 OK", content_type, &body),
+        None => write_response(&mut stream, "404 Not Found", "text/plain", b"Not Found"),
+    }
+}
+
+fn write_response(
+    stream: &mut TcpStream,
+    status: &str,
+    content_type: &str,
+    body: &[u8],
+) -> io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        status,
+        content_type,
+        body.len()
+    )?;
+    stream.write_all(body)?;
+    stream.flush()
+}
+
+fn respond(fixtures: &Path, target: &str) -> Option<(&'static str, Vec<u8>)> {
+    let (path, query) = match target.split_once('?') {
+        Some((path, query)) => (path, parse_query(query)),
+        None => (target, HashMap::new()),
+    };
+    let path = path.trim_matches('/');
+
+    // Keep requests inside the fixture directory
+    if path.split('/').any(|x| x == "..") || query.values().any(|x| x.contains(['/', '\\'])) {
+        return None;
+    }
+
+    match path {
+        "data/download" => {
+            let data_type = query.get("data_type")?;
+            let key = match data_type.as_str() {
+                "lightning" => query.get("lightning_type")?,
+                data_type => query.get(&format!("station_{}", data_type))?,
+            };
+            let file = fixtures
+                .join("data/download")
+                .join(data_type)
+                .join(format!("{}.tsv", key));
+
+            Some(("text/tab-separated-values", filter_tsv(&file, &query, 2)?))
+        }
+        "data/network/coincidences" => {
+            let file = fixtures.join("data/network/coincidences.tsv");
+
+            // Coincidence ID and station number precede the event columns
+            Some(("text/tab-separated-values", filter_tsv(&file, &query, 4)?))
+        }
+        path => {
+            let file = fixtures.join(format!("{}.json", path));
+            Some(("application/json", fs::read(file).ok()?))
+        }
+    }
+}
+
+fn filter_tsv(
+    file: &Path,
+    query: &HashMap<String, String>,
+    timestamp_column: usize,
+) -> Option<Vec<u8>> {
+    let text = fs::read_to_string(file).ok()?;
+
+    let start = query.get("start").and_then(|x| parse_query_datetime(x));
+    let end = query.get("end").and_then(|x| parse_query_datetime(x));
+
+    let mut output = String::new();
+    for line in text.lines() {
+        if !line.starts_with('#') && !line.is_empty() {
+            let timestamp: Option<i64> = line
+                .split('\t')
+                .nth(timestamp_column)
+                .and_then(|x| x.parse().ok());
+
+            if let Some(timestamp) = timestamp {
+                if start.is_some_and(|start| timestamp < start)
+                    || end.is_some_and(|end| timestamp >= end)
+                {
+                    continue;
+                }
+            }
+        }
+
+        output.push_str(line);
+        output.push('\n');
+    }
+
+    Some(output.into_bytes())
+}
+
+fn parse_query_datetime(text: &str) -> Option<i64> {
+    NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M:%S")
+        .ok()
+        .map(|x| x.and_utc().timestamp())
+}
+
+fn parse_query(query: &str) -> HashMap<String, String> {
+    query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .map(|(key, value)| (percent_decode(key), percent_decode(value)))
+        .collect()
+}
+
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => decoded.push(b' '),
+            b'%' if i + 2 < bytes.len() => {
+                let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).unwrap_or_default();
+                match u8::from_str_radix(hex, 16) {
+                    Ok(byte) => {
+                        decoded.push(byte);
+                        i += 2;
+                    }
+                    Err(_) => decoded.push(b'%'),
+                }
+            }
+            byte => decoded.push(byte),
+        }
+        i += 1;
+    }
+
+    String::from_utf8_lossy(&decoded).into_owned()
+}
@@ -0,0 +1,343 @@
+use crate::error::{HisparcError, Result};
+use chrono::{DateTime, Utc};
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use reqwest::StatusCode;
+use std::collections::hash_map::RandomState;
+use std::collections::HashSet;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    multiplier: f64,
+    jitter: bool,
+    retry_on_status: HashSet<u16>,
+}
+
+// Spaces requests out so that at most one starts per interval. Clones share
+// their state, so one limiter can be given to several clients.
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+    interval: Duration,
+    next_request: Arc<Mutex<Option<Instant>>>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+            multiplier: 2.0,
+            jitter: true,
+            retry_on_status: [408, 429, 500, 502, 503, 504].into_iter().collect(),
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Sends every request exactly once
+    pub fn never() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Self::default()
+        }
+    }
+
+    // The total number of attempts, including the first one
+    pub fn max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    pub fn initial_backoff(mut self, backoff: Duration) -> Self {
+        self.initial_backoff = backoff;
+        self
+    }
+
+    pub fn max_backoff(mut self, backoff: Duration) -> Self {
+        self.max_backoff = backoff;
+        self
+    }
+
+    pub fn multiplier(mut self, multiplier: f64) -> Self {
+        self.multiplier = multiplier.max(1.0);
+        self
+    }
+
+    // With jitter, each wait is drawn uniformly between zero and the
+    // exponential backoff, so that many clients do not retry in lockstep.
+    pub fn jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    pub fn retry_on_status<I: IntoIterator<Item = u16>>(mut self, statuses: I) -> Self {
+        self.retry_on_status = statuses.into_iter().collect();
+        self
+    }
+
+    pub fn attempts(&self) -> u32 {
+        self.max_attempts
+    }
+
+    // The wait before the given retry, counting the first retry as 1
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let exponent = retry.saturating_sub(1).min(i32::MAX as u32) as i32;
+        let backoff = self
+            .initial_backoff
+            .mul_f64(self.multiplier.powi(exponent).min(u32::MAX as f64))
+            .min(self.max_backoff);
+
+        if self.jitter {
+            backoff.mul_f64(random_fraction())
+        } else {
+            backoff
+        }
+    }
+
+    // The wait before the given retry when the server asked for one with a
+    // Retry-After header. It is capped at the maximum backoff.
+    pub fn backoff_with(&self, retry: u32, retry_after: Option<Duration>) -> Duration {
+        match retry_after {
+            Some(retry_after) => retry_after.min(self.max_backoff),
+            None => self.backoff(retry),
+        }
+    }
+
+    pub fn retries_status(&self, status: u16) -> bool {
+        self.retry_on_status.contains(&status)
+    }
+
+    pub(crate) fn should_retry(&self, error: &reqwest::Error) -> bool {
+        match error.status() {
+            Some(status) => self.retries_status(status.as_u16()),
+            None => error.is_timeout() || error.is_connect() || error.is_body(),
+        }
+    }
+}
+
+// The Retry-After header of the last failed response of a request. Clones
+// share it, so it can be handed to each attempt.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct RetryAfter(Arc<Mutex<Option<Duration>>>);
+
+impl RetryAfter {
+    pub(crate) fn observe(&self, status: StatusCode, headers: &HeaderMap) {
+        if status.is_client_error() || status.is_server_error() {
+            *self.0.lock().unwrap() = headers
+                .get(RETRY_AFTER)
+                .and_then(|x| x.to_str().ok())
+                .and_then(|x| parse_retry_after(x, Utc::now()));
+        }
+    }
+
+    pub(crate) fn take(&self) -> Option<Duration> {
+        self.0.lock().unwrap().take()
+    }
+}
+
+// Either a number of seconds or an HTTP date
+pub(crate) fn parse_retry_after(value: &str, now: DateTime<Utc>) -> Option<Duration> {
+    let value = value.trim();
+
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    let date = DateTime::parse_from_rfc2822(value).ok()?;
+    Some(
+        (date.with_timezone(&Utc) - now)
+            .to_std()
+            .unwrap_or(Duration::ZERO),
+    )
+}
+
+impl RateLimiter {
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            next_request: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    // requests must be positive, and small enough numbers to give a
+    // representable interval
+    pub fn per_second(requests: f64) -> Result<Self> {
+        Duration::try_from_secs_f64(1.0 / requests)
+            .map(Self::new)
+            .map_err(|_| {
+                HisparcError::InvalidInput(format!("Cannot make {} requests per second", requests))
+            })
+    }
+
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    // Reserves the next slot and returns how long to wait for it
+    pub(crate) fn reserve(&self) -> Duration {
+        let mut next_request = self.next_request.lock().unwrap();
+        let now = Instant::now();
+
+        let slot = match *next_request {
+            Some(next) if next > now => next,
+            _ => now,
+        };
+        *next_request = Some(slot + self.interval);
+
+        slot - now
+    }
+
+    pub(crate) fn wait(&self) {
+        let wait = self.reserve();
+        if !wait.is_zero() {
+            std::thread::sleep(wait);
+        }
+    }
+}
+
+fn random_fraction() -> f64 {
+    // RandomState is seeded randomly for every instance, which is plenty for
+    // jitter and saves a dependency.
+    let random = RandomState::new().build_hasher().finish();
+    (random >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    fn millis(ms: u64) -> Duration {
+        Duration::from_millis(ms)
+    }
+
+    fn steady_policy() -> RetryPolicy {
+        RetryPolicy::new()
+            .initial_backoff(millis(100))
+            .max_backoff(millis(1000))
+            .multiplier(2.0)
+            .jitter(false)
+    }
+
+    #[test]
+    fn backoff_grows_up_to_the_cap() {
+        let policy = steady_policy();
+        let waits: Vec<Duration> = (1..=6).map(|x| policy.backoff(x)).collect();
+        assert_eq!(
+            waits,
+            vec![
+                millis(100),
+                millis(200),
+                millis(400),
+                millis(800),
+                millis(1000),
+                millis(1000)
+            ]
+        );
+
+        // Huge retry counts must not overflow
+        assert_eq!(policy.backoff(u32::MAX), millis(1000));
+    }
+
+    #[test]
+    fn jitter_stays_between_zero_and_the_backoff() {
+        let policy = steady_policy().jitter(true);
+
+        for retry in 1..=6 {
+            let limit = steady_policy().backoff(retry);
+            for _ in 0..100 {
+                assert!(policy.backoff(retry) <= limit);
+            }
+        }
+    }
+
+    #[test]
+    fn only_configured_statuses_are_retried() {
+        let policy = RetryPolicy::new();
+        assert!(policy.retries_status(503));
+        assert!(policy.retries_status(429));
+        assert!(!policy.retries_status(404));
+        assert!(!policy.retries_status(400));
+
+        let policy = policy.retry_on_status([500]);
+        assert!(policy.retries_status(500));
+        assert!(!policy.retries_status(503));
+    }
+
+    #[test]
+    fn retry_after_replaces_the_backoff() {
+        let now = DateTime::parse_from_rfc2822("Wed, 17 May 2023 12:00:00 GMT")
+            .unwrap()
+            .with_timezone(&Utc);
+
+        assert_eq!(parse_retry_after("7", now), Some(Duration::from_secs(7)));
+        assert_eq!(
+            parse_retry_after("Wed, 17 May 2023 12:00:30 GMT", now),
+            Some(Duration::from_secs(30))
+        );
+        assert_eq!(
+            parse_retry_after("Wed, 17 May 2023 11:00:00 GMT", now),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(parse_retry_after("soon", now), None);
+
+        let policy = steady_policy();
+        assert_eq!(policy.backoff_with(1, Some(millis(300))), millis(300));
+        assert_eq!(policy.backoff_with(1, Some(millis(5000))), millis(1000));
+        assert_eq!(policy.backoff_with(3, None), millis(400));
+    }
+
+    #[test]
+    fn retry_after_is_only_noted_for_failures() {
+        let mut headers = HeaderMap::new();
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("2"));
+
+        let retry_after = RetryAfter::default();
+        retry_after.observe(StatusCode::OK, &headers);
+        assert_eq!(retry_after.take(), None);
+
+        retry_after.observe(StatusCode::SERVICE_UNAVAILABLE, &headers);
+        assert_eq!(retry_after.clone().take(), Some(Duration::from_secs(2)));
+        assert_eq!(retry_after.take(), None);
+    }
+
+    #[test]
+    fn rate_limiter_rejects_invalid_rates() {
+        for rate in [0.0, -1.0, f64::NAN, -0.0] {
+            assert!(matches!(
+                RateLimiter::per_second(rate),
+                Err(HisparcError::InvalidInput(_))
+            ));
+        }
+
+        assert_eq!(
+            RateLimiter::per_second(4.0).unwrap().interval(),
+            millis(250)
+        );
+    }
+
+    #[test]
+    fn rate_limiter_spaces_out_requests() {
+        let limiter = RateLimiter::new(millis(100));
+        let shared = limiter.clone();
+
+        assert_eq!(limiter.reserve(), Duration::ZERO);
+
+        // Each reservation waits one interval longer than the one before,
+        // give or take the time spent in between
+        let second = shared.reserve();
+        let third = limiter.reserve();
+        assert!(second > millis(90) && second <= millis(100));
+        assert!(third > millis(190) && third <= millis(200));
+    }
+}
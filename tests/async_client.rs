@@ -0,0 +1,85 @@
+use futures_util::StreamExt;
+use hisparc::api::StationQuery;
+use hisparc::mock::MockServer;
+use hisparc::AsyncHisparcClient;
+
+mod common;
+use common::{date, datetime, fixtures};
+
+fn start() -> (MockServer, AsyncHisparcClient) {
+    let server = MockServer::start(fixtures()).unwrap();
+    let client = server.client_builder().build_async().unwrap();
+    (server, client)
+}
+
+#[tokio::test]
+async fn api_endpoints_are_fetched_asynchronously() {
+    let (_server, client) = start();
+
+    let stations = client.get_stations().await.unwrap();
+    assert_eq!(stations.len(), 3);
+    assert_eq!(stations[0].number, 501);
+
+    let info = client
+        .get_station_info(501, date(2023, 5, 17))
+        .await
+        .unwrap();
+    assert_eq!(info.subcluster, "Science Park");
+    assert_eq!(info.scintillators.len(), 4);
+
+    let error = client.get_station_info(9999, None).await.unwrap_err();
+    assert!(error.is_not_found());
+}
+
+#[tokio::test]
+async fn events_are_streamed_asynchronously() {
+    let (_server, client) = start();
+    let start = datetime("2023-05-17 00:00:00");
+    let end = datetime("2023-05-18 00:00:00");
+
+    let mut stream = client.get_event_stream(501, start, end).await.unwrap();
+    let mut events = Vec::new();
+    while let Some(event) = stream.next_event().await {
+        events.push(event.unwrap());
+    }
+    assert_eq!(events.len(), 2);
+    assert!(events[0].reconstructed_angle().is_some());
+    assert_eq!(stream.into_metadata().station_number, Some(501));
+
+    // The same events through the Stream adapter
+    let streamed: Vec<_> = client
+        .get_event_stream(501, start, end)
+        .await
+        .unwrap()
+        .into_stream()
+        .collect()
+        .await;
+    assert_eq!(streamed.len(), 2);
+    assert!(streamed.iter().all(|x| x.is_ok()));
+
+    let collected = client.get_event_data(501, start, end).await.unwrap();
+    assert_eq!(collected.len(), 2);
+}
+
+#[tokio::test]
+async fn catalogue_is_loaded_and_searched_asynchronously() {
+    let (_server, client) = start();
+    let catalogue = client.get_station_catalogue(None).await.unwrap();
+
+    let numbers: Vec<u32> = catalogue.stations.iter().map(|x| x.number).collect();
+    assert_eq!(numbers, vec![501, 502, 7001]);
+
+    let query = StationQuery::new()
+        .active(true)
+        .has_weather_on(date(2023, 5, 17));
+    let found = catalogue.search_async(&client, &query).await.unwrap();
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].number, 501);
+
+    let data = StationQuery::new().has_data_on(date(2023, 5, 18));
+    assert!(catalogue
+        .search_async(&client, &data)
+        .await
+        .unwrap()
+        .is_empty());
+}
@@ -0,0 +1,118 @@
+use chrono::{Datelike, Utc};
+use hisparc::api::Period;
+use hisparc::mock::MockServer;
+use hisparc::ResponseCache;
+use std::fs;
+use std::time::Duration;
+
+mod common;
+use common::{date, fixtures, temp_dir};
+
+#[test]
+fn cached_responses_are_served_from_disk() {
+    let dir = temp_dir("cache");
+    let server = MockServer::start(fixtures()).unwrap();
+    let cache = ResponseCache::new(&dir).ttl(Duration::from_secs(60));
+    let client = server
+        .client_builder()
+        .cache(cache.clone())
+        .build()
+        .unwrap();
+
+    let station_info_requests = || {
+        server
+            .requests()
+            .iter()
+            .filter(|x| *x == "/api/station/501/2023/5/17/")
+            .count()
+    };
+
+    let info = client.get_station_info(501, date(2023, 5, 17)).unwrap();
+    let cached = client.get_station_info(501, date(2023, 5, 17)).unwrap();
+    assert_eq!(cached.name, info.name);
+    assert_eq!(station_info_requests(), 1);
+
+    client.get_stations().unwrap();
+
+    // Past dates never expire, lists of stations do
+    let entries = cache.entries().unwrap();
+    assert_eq!(entries.len(), 2);
+    let info_entry = cache
+        .get_entry(&format!("{}station/501/2023/5/17/", server.api_url()))
+        .unwrap();
+    assert_eq!(info_entry.expires_at, None);
+    let stations_entry = cache
+        .get_entry(&format!("{}stations/", server.api_url()))
+        .unwrap();
+    assert!(stations_entry.expires_at.is_some());
+
+    assert_eq!(cache.purge_expired().unwrap(), 0);
+    assert_eq!(cache.purge().unwrap(), 2);
+    assert!(cache.entries().unwrap().is_empty());
+
+    client.get_station_info(501, date(2023, 5, 17)).unwrap();
+    assert_eq!(station_info_requests(), 2);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn only_past_months_are_kept_forever() {
+    let dir = temp_dir("cache-period");
+    let today = Utc::now().date_naive();
+
+    // The fixtures plus a count for the current month
+    let fixture_dir = temp_dir("cache-period-fixtures");
+    let num_events = fixture_dir.join("api/station/501/num_events");
+    let this_year = num_events.join(today.year().to_string());
+    fs::create_dir_all(num_events.join("2023")).unwrap();
+    fs::create_dir_all(&this_year).unwrap();
+    fs::copy(
+        format!("{}/api.json", fixtures()),
+        fixture_dir.join("api.json"),
+    )
+    .unwrap();
+    fs::write(num_events.join("2023/5.json"), "3").unwrap();
+    fs::write(this_year.join(format!("{}.json", today.month())), "5").unwrap();
+    fs::write(fixture_dir.join("api/station/501/num_events.json"), "8").unwrap();
+
+    let server = MockServer::start(&fixture_dir).unwrap();
+    let cache = ResponseCache::new(&dir)
+        .ttl(Duration::from_secs(24 * 60 * 60))
+        .today_ttl(Duration::from_secs(60));
+    let client = server
+        .client_builder()
+        .cache(cache.clone())
+        .build()
+        .unwrap();
+
+    let expires_at = |path: String| {
+        cache
+            .get_entry(&format!("{}{}", server.api_url(), path))
+            .unwrap()
+            .expires_at
+    };
+    let soon = Utc::now() + chrono::Duration::seconds(61);
+
+    let past = client
+        .get_number_of_events(501, Period::Month(2023, 5))
+        .unwrap();
+    assert_eq!(past, 3);
+    assert_eq!(
+        expires_at("station/501/num_events/2023/5/".to_string()),
+        None
+    );
+
+    let current = client
+        .get_number_of_events(501, Period::Month(today.year(), today.month()))
+        .unwrap();
+    assert_eq!(current, 5);
+    let path = format!("station/501/num_events/{}/{}/", today.year(), today.month());
+    assert!(expires_at(path).unwrap() < soon);
+
+    assert_eq!(client.get_number_of_events(501, Period::All).unwrap(), 8);
+    assert!(expires_at("station/501/num_events/".to_string()).unwrap() < soon);
+
+    fs::remove_dir_all(&dir).unwrap();
+    fs::remove_dir_all(&fixture_dir).unwrap();
+}
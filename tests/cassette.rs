@@ -0,0 +1,118 @@
+use hisparc::mock::MockServer;
+use hisparc::{Cassette, HisparcClient, HisparcError, ResponseCache, RetryPolicy};
+
+mod common;
+use common::{date, datetime, fixtures, temp_dir};
+
+#[test]
+fn replayed_responses_match_the_recording() {
+    let dir = temp_dir("replay");
+    let start = datetime("2023-05-17 00:00:00");
+    let end = datetime("2023-05-18 00:00:00");
+
+    let server = MockServer::start(fixtures()).unwrap();
+    let (api_url, data_url) = (server.api_url(), server.data_url());
+
+    let recorder = server
+        .client_builder()
+        .cassette(Cassette::record(&dir))
+        .build()
+        .unwrap();
+
+    let info = recorder.get_station_info(501, date(2023, 5, 17)).unwrap();
+    let events = recorder.get_event_data(501, start, end).unwrap();
+    let weather = recorder.get_weather_data(501, start, end).unwrap();
+    let missing = recorder
+        .get_station_info(9999, date(2023, 5, 17))
+        .unwrap_err();
+    assert!(missing.is_not_found());
+
+    // Nothing is listening any more, so every response must come from the
+    // cassette
+    drop(server);
+
+    let player = HisparcClient::builder()
+        .api_url(api_url)
+        .data_url(data_url)
+        .retry_policy(RetryPolicy::never())
+        .cassette(Cassette::replay(&dir))
+        .build()
+        .unwrap();
+
+    let replayed_info = player.get_station_info(501, date(2023, 5, 17)).unwrap();
+    assert_eq!(replayed_info.name, info.name);
+    assert_eq!(replayed_info.scintillators.len(), info.scintillators.len());
+
+    assert_eq!(player.get_event_data(501, start, end).unwrap(), events);
+    assert_eq!(
+        player.get_weather_data(501, start, end).unwrap().len(),
+        weather.len()
+    );
+    assert!(player
+        .get_station_info(9999, date(2023, 5, 17))
+        .unwrap_err()
+        .is_not_found());
+
+    let error = player.get_event_data(502, start, end).unwrap_err();
+    assert!(matches!(error, HisparcError::NotRecorded { .. }));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn cassette_bypasses_the_response_cache() {
+    let cassette_dir = temp_dir("cassette-with-cache");
+    let cache_dir = temp_dir("cache-with-cassette");
+    let cache = ResponseCache::new(&cache_dir);
+
+    let server = MockServer::start(fixtures()).unwrap();
+    let (api_url, data_url) = (server.api_url(), server.data_url());
+
+    // Fill the cache first, so a cache hit would keep the request from the
+    // cassette
+    let cached = server
+        .client_builder()
+        .cache(cache.clone())
+        .build()
+        .unwrap();
+    cached.get_station_info(501, date(2023, 5, 17)).unwrap();
+    assert_eq!(cache.entries().unwrap().len(), 1);
+
+    let recorder = server
+        .client_builder()
+        .cache(cache.clone())
+        .cassette(Cassette::record(&cassette_dir))
+        .build()
+        .unwrap();
+    recorder.get_station_info(501, date(2023, 5, 17)).unwrap();
+
+    let url = format!("{}station/501/2023/5/17/", api_url);
+    assert!(Cassette::replay(&cassette_dir)
+        .urls()
+        .unwrap()
+        .contains(&url));
+    drop(server);
+
+    // A cache entry that disagrees with the cassette is not used
+    for file in std::fs::read_dir(&cache_dir).unwrap() {
+        let path = file.unwrap().path();
+        if path.extension().is_some_and(|x| x == "json") {
+            let body = std::fs::read_to_string(&path).unwrap();
+            std::fs::write(&path, body.replace("Nikhef", "Cached")).unwrap();
+        }
+    }
+
+    let player = HisparcClient::builder()
+        .api_url(api_url)
+        .data_url(data_url)
+        .retry_policy(RetryPolicy::never())
+        .cache(cache)
+        .cassette(Cassette::replay(&cassette_dir))
+        .build()
+        .unwrap();
+    let info = player.get_station_info(501, date(2023, 5, 17)).unwrap();
+    assert_eq!(info.name, "Nikhef");
+
+    std::fs::remove_dir_all(&cassette_dir).unwrap();
+    std::fs::remove_dir_all(&cache_dir).unwrap();
+}
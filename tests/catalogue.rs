@@ -0,0 +1,59 @@
+use hisparc::api::{StationCatalogue, StationQuery};
+use hisparc::mock::MockServer;
+
+mod common;
+use common::{date, fixtures};
+
+fn numbers<'a, I: IntoIterator<Item = &'a hisparc::api::StationInfo>>(stations: I) -> Vec<u32> {
+    stations.into_iter().map(|x| x.number).collect()
+}
+
+#[test]
+fn catalogue_is_searched_by_info_position_and_weather() {
+    let server = MockServer::start(fixtures()).unwrap();
+    let client = server.client().unwrap();
+    let catalogue = client.get_station_catalogue(None).unwrap();
+
+    assert_eq!(numbers(&catalogue.stations), vec![501, 502, 7001]);
+
+    let active = StationQuery::new().active(true);
+    assert_eq!(numbers(catalogue.filter(&active)), vec![501, 502]);
+
+    let eindhoven = StationQuery::new()
+        .cluster("eindhoven")
+        .country("Netherlands");
+    assert_eq!(numbers(catalogue.filter(&eindhoven)), vec![7001]);
+
+    let named = StationQuery::new().name_contains("HOVEN");
+    assert_eq!(numbers(catalogue.filter(&named)), vec![7001]);
+
+    let four = StationQuery::new().scintillators(4..);
+    assert_eq!(numbers(catalogue.filter(&four)), vec![501, 7001]);
+
+    let amsterdam = StationQuery::new().within_bounding_box(52.2, 4.7, 52.5, 5.1);
+    assert_eq!(numbers(catalogue.filter(&amsterdam)), vec![501, 502]);
+
+    // Stations within 5 km of Science Park with weather on a given day
+    let (latitude, longitude) = catalogue.station(501).unwrap().position().unwrap();
+    let query = StationQuery::new()
+        .within_km(latitude, longitude, 5.0)
+        .has_weather_on(date(2023, 5, 17));
+    assert_eq!(
+        numbers(catalogue.search(&client, &query).unwrap()),
+        vec![501]
+    );
+
+    let distance = catalogue
+        .station(7001)
+        .unwrap()
+        .distance_km(latitude, longitude)
+        .unwrap();
+    assert!((100.0..115.0).contains(&distance), "{}", distance);
+
+    // No station had data on this day, which the API answers with a 404
+    let data = StationQuery::new().has_data_on(date(2023, 5, 18));
+    assert!(catalogue.search(&client, &data).unwrap().is_empty());
+
+    let json = catalogue.to_json().unwrap();
+    assert_eq!(StationCatalogue::from_json(&json).unwrap(), catalogue);
+}
@@ -0,0 +1,26 @@
+// Helpers shared by the integration tests. Not every test uses all of them.
+#![allow(dead_code)]
+
+use chrono::{prelude::DateTime, NaiveDate, NaiveDateTime, Utc};
+use std::path::PathBuf;
+
+pub fn fixtures() -> String {
+    format!("{}/tests/fixtures", env!("CARGO_MANIFEST_DIR"))
+}
+
+// An empty directory under the system temp dir, unique to this test process
+pub fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("hisparc-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+pub fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(year, month, day).unwrap()
+}
+
+pub fn datetime(text: &str) -> DateTime<Utc> {
+    NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M:%S")
+        .unwrap()
+        .and_utc()
+}
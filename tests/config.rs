@@ -0,0 +1,56 @@
+use hisparc::api::StationConfig;
+use hisparc::mock::MockServer;
+
+mod common;
+use common::{date, datetime, fixtures};
+
+#[test]
+fn flat_configuration_is_read_into_nested_structs() {
+    let server = MockServer::start(fixtures()).unwrap();
+    let config = server
+        .client()
+        .unwrap()
+        .get_configuration(501, date(2023, 5, 17))
+        .unwrap();
+
+    assert_eq!(config.station_number, 501);
+    assert_eq!(config.timestamp, datetime("2023-05-17 08:12:33"));
+    assert_eq!(config.trigger.low_signals, 2);
+    assert!(!config.trigger.and_or);
+    assert!((config.gps.latitude - 52.3559).abs() < 1e-3);
+
+    let master = &config.electronics.master;
+    assert_eq!(master.version, "Hardware: 37 FPGA: 21");
+    assert_eq!(master.channels[0].voltage, 736.0);
+    assert_eq!(master.channels[1].thresholds.high, 323.0);
+    assert_eq!(master.channels[1].offsets.positive, -22.0);
+    assert_eq!(config.electronics.slave.channels[1].voltage, 742.0);
+
+    // Serializes back to the flat layout of the API
+    let json = serde_json::to_value(&config).unwrap();
+    assert_eq!(json["mas_ch1_voltage"], 736.0);
+    assert_eq!(json["timestamp"], "2023-05-17 08:12:33");
+    let back: StationConfig = serde_json::from_value(json).unwrap();
+    assert_eq!(back, config);
+}
+
+#[test]
+fn integers_and_flags_written_as_floats_are_accepted() {
+    let path = format!("{}/api/station/501/config/2023/5/17.json", fixtures());
+    let mut json: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+
+    json["detnum"] = 501.0.into();
+    json["trig_and_or"] = 1.0.into();
+    json["timestamp"] = "2023-05-17T08:12:33Z".into();
+    let config: StationConfig = serde_json::from_value(json.clone()).unwrap();
+    assert_eq!(config.station_number, 501);
+    assert!(config.trigger.and_or);
+
+    json["detnum"] = 501.5.into();
+    let error = serde_json::from_value::<StationConfig>(json.clone()).unwrap_err();
+    assert!(error.to_string().contains("detnum"));
+
+    json.as_object_mut().unwrap().remove("slv_ch2_voltage");
+    assert!(serde_json::from_value::<StationConfig>(json).is_err());
+}
@@ -0,0 +1,91 @@
+use hisparc::mock::MockServer;
+use hisparc::{HisparcClient, RetryPolicy};
+use std::time::Duration;
+
+mod common;
+use common::fixtures;
+
+fn root_requests(server: &MockServer) -> usize {
+    server.requests().iter().filter(|x| *x == "/api/").count()
+}
+
+#[test]
+fn failing_root_falls_back_to_the_known_endpoints() {
+    let server = MockServer::start(fixtures()).unwrap();
+    server.fail("/api/", 500);
+    let client = server.client().unwrap();
+
+    assert_eq!(
+        client.get_api_url("stations").unwrap(),
+        format!("{}stations/", server.api_url())
+    );
+    assert_eq!(client.get_stations().unwrap().len(), 3);
+
+    // The fallback is kept for a while rather than retried on every call
+    assert_eq!(root_requests(&server), 1);
+}
+
+#[test]
+fn unreachable_root_falls_back_to_the_known_endpoints() {
+    let client = HisparcClient::builder()
+        .api_url("http://127.0.0.1:1/api/")
+        .retry_policy(RetryPolicy::never())
+        .build()
+        .unwrap();
+
+    assert_eq!(
+        client.get_api_url("station_info").unwrap(),
+        "http://127.0.0.1:1/api/station/{station_number}/{year}/{month}/{day}/"
+    );
+
+    let error = client.get_stations().unwrap_err();
+    assert!(error.is_server_unavailable());
+    assert!(error
+        .to_string()
+        .contains("http://127.0.0.1:1/api/stations/"));
+}
+
+#[test]
+fn discovery_is_retried_after_the_interval() {
+    let server = MockServer::start(fixtures()).unwrap();
+    server.fail("/api/", 503);
+    let client = server
+        .client_builder()
+        .discovery_retry_interval(Duration::from_millis(200))
+        .build()
+        .unwrap();
+
+    client.get_stations().unwrap();
+    client.get_stations().unwrap();
+    assert_eq!(root_requests(&server), 1);
+
+    server.recover("/api/");
+    std::thread::sleep(Duration::from_millis(300));
+
+    client.get_stations().unwrap();
+    assert_eq!(root_requests(&server), 2);
+
+    // Discovered endpoints are kept
+    std::thread::sleep(Duration::from_millis(300));
+    client.get_stations().unwrap();
+    assert_eq!(root_requests(&server), 2);
+}
+
+#[test]
+fn refresh_rediscovers_after_recovery() {
+    let server = MockServer::start(fixtures()).unwrap();
+    server.fail("/api/", 500);
+    let client = server.client().unwrap();
+
+    client.get_stations().unwrap();
+
+    let error = client.refresh_api_urls().unwrap_err();
+    assert!(error.is_server_unavailable());
+
+    server.recover("/api/");
+    client.refresh_api_urls().unwrap();
+    assert_eq!(root_requests(&server), 3);
+
+    client.get_stations().unwrap();
+    assert_eq!(root_requests(&server), 3);
+}
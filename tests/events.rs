@@ -0,0 +1,52 @@
+use hisparc::data::{read_events_from_path, read_events_from_reader};
+use std::fs::File;
+
+mod common;
+use common::fixtures;
+
+const HEADER: &str = "date\ttime\ttimestamp\tnanoseconds\tpulseheights1\tpulseheights2\tpulseheights3\tpulseheights4\tintegrals1\tintegrals2\tintegrals3\tintegrals4\tn1\tn2\tn3\tn4\tt1\tt2\tt3\tt4\tt_trigger\tzenith\tazimuth";
+const EVENT: &str = "2023-05-17\t00:00:12\t1684281612\t123456789\t310\t254\t401\t188\t3511\t2897\t4788\t2103\t1.12\t0.91\t1.43\t0.67\t12.5\t15.0\t10.0\t17.5\t30.0\t0.4363\t1.0472";
+
+#[test]
+fn uncommented_header_rows_are_read() {
+    let input = format!("{}\n{}\n", HEADER, EVENT);
+    let events = read_events_from_reader(input.as_bytes()).unwrap();
+    assert_eq!(events.len(), 1);
+}
+
+#[test]
+fn lines_that_are_neither_header_nor_event_are_errors() {
+    let html = "<html>\n<body>502 Bad Gateway</body>\n</html>\n";
+    let error = read_events_from_reader(html.as_bytes()).unwrap_err();
+    assert_eq!(error.line(), Some(1));
+
+    let malformed = format!("garbage\tin\tthe\tfirst\trow\n{}\n", EVENT);
+    let error = read_events_from_reader(malformed.as_bytes()).unwrap_err();
+    assert_eq!(error.line(), Some(1));
+}
+
+#[test]
+fn gzipped_files_give_the_same_events() {
+    let plain = format!("{}/data/download/events/501.tsv", fixtures());
+    let gzipped = format!("{}/files/501.tsv.gz", fixtures());
+
+    let events = read_events_from_path(&plain).unwrap();
+    assert_eq!(events.len(), 3);
+    assert_eq!(read_events_from_path(&gzipped).unwrap(), events);
+
+    let reader = File::open(&gzipped).unwrap();
+    assert_eq!(read_events_from_reader(reader).unwrap(), events);
+}
+
+#[test]
+fn events_survive_a_json_round_trip() {
+    // Detector 2 is missing and the angle could not be reconstructed
+    let row = "2023-05-17\t00:30:00\t1684283400\t500\t290\t-1\t366\t205\t3320\t-1\t4102\t2280\t1.04\t-1\t1.31\t0.73\t10.0\t-999\t7.5\t22.5\t25.0\t-999\t-999";
+    let events = read_events_from_reader(format!("{}\n{}\n", HEADER, row).as_bytes()).unwrap();
+    assert_eq!(events[0].pulseheights().detector_2(), None);
+    assert!(events[0].reconstructed_angle().is_none());
+
+    let json = serde_json::to_string(&events).unwrap();
+    let read_back: Vec<hisparc::data::Event> = serde_json::from_str(&json).unwrap();
+    assert_eq!(read_back, events);
+}
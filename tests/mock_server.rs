@@ -0,0 +1,244 @@
+use hisparc::api::Period;
+use hisparc::data::{ChunkedDownloader, LightningType, StationSelection};
+use hisparc::mock::MockServer;
+use hisparc::{HisparcClient, HisparcError, ResponseCache};
+
+mod common;
+use common::{date, datetime, fixtures, temp_dir};
+
+fn start() -> (MockServer, HisparcClient) {
+    let server = MockServer::start(fixtures()).unwrap();
+    let client = server.client().unwrap();
+    (server, client)
+}
+
+#[test]
+fn api_endpoints_are_discovered_from_the_api_root() {
+    let (server, client) = start();
+
+    let stations = client.get_stations().unwrap();
+    assert_eq!(stations.len(), 3);
+    assert_eq!(stations[0].number, 501);
+
+    let info = client.get_station_info(501, date(2023, 5, 17)).unwrap();
+    assert_eq!(info.subcluster, "Science Park");
+    assert_eq!(info.scintillators.len(), 4);
+
+    assert!(client.get_has_data(501, date(2023, 5, 17)).unwrap());
+    assert!(!client.get_has_singles(502, date(2023, 5, 17)).unwrap());
+    assert_eq!(
+        client
+            .get_number_of_events(501, datetime("2023-05-17 00:00:00").naive_utc())
+            .unwrap(),
+        3
+    );
+
+    assert_eq!(server.requests()[0], "/api/");
+}
+
+#[test]
+fn dates_can_be_left_out_or_coarsened() {
+    let (server, client) = start();
+
+    let latest = client.get_station_info(501, None).unwrap();
+    assert_eq!(latest.number, 501);
+    assert!(client.get_has_data(501, None).unwrap());
+
+    assert_eq!(client.get_number_of_events(501, Period::All).unwrap(), 7);
+    assert_eq!(
+        client
+            .get_number_of_events(501, Period::Month(2023, 5))
+            .unwrap(),
+        5
+    );
+    assert_eq!(
+        client.get_number_of_events(501, date(2023, 5, 17)).unwrap(),
+        4
+    );
+    assert!(client
+        .get_number_of_events(501, Period::Month(2023, 13))
+        .is_err());
+
+    let requests = server.requests();
+    assert!(requests.contains(&"/api/station/501/".to_string()));
+    assert!(requests.contains(&"/api/station/501/num_events/2023/5/".to_string()));
+    assert!(!requests.iter().any(|x| x.contains("/13/")));
+}
+
+#[test]
+fn missing_fixtures_are_not_found() {
+    let (_server, client) = start();
+
+    let error = client
+        .get_station_info(9999, date(2023, 5, 17))
+        .unwrap_err();
+    assert!(error.is_not_found());
+}
+
+#[test]
+fn events_are_limited_to_the_requested_range() {
+    let (_server, client) = start();
+
+    let (metadata, events) = client
+        .get_event_data_with_metadata(
+            501,
+            datetime("2023-05-17 00:00:00"),
+            datetime("2023-05-18 00:00:00"),
+        )
+        .unwrap();
+
+    assert_eq!(metadata.station_number, Some(501));
+    assert_eq!(events.len(), 2);
+    assert!(events[0].reconstructed_angle().is_some());
+}
+
+#[test]
+fn missing_detectors_and_reconstructions_are_none() {
+    let (_server, client) = start();
+
+    let events = client
+        .get_event_data(
+            501,
+            datetime("2023-05-17 00:30:00"),
+            datetime("2023-05-17 01:00:00"),
+        )
+        .unwrap();
+
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].pulseheights().detector_2(), None);
+    assert_eq!(events[0].arrival_times().detector_2(), None);
+    assert_eq!(events[0].number_of_detectors(), 3);
+    assert!(events[0].reconstructed_angle().is_none());
+}
+
+#[test]
+fn two_detector_stations_use_the_legacy_layout() {
+    let (_server, client) = start();
+
+    let events = client
+        .get_event_data(
+            502,
+            datetime("2023-05-17 00:00:00"),
+            datetime("2023-05-18 00:00:00"),
+        )
+        .unwrap();
+
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[0].number_of_detectors(), 2);
+    assert_eq!(events[1].pulseheights().detector_1(), None);
+    assert_eq!(events[1].pulseheights().detector_3(), None);
+}
+
+#[test]
+fn weather_and_coincidences_are_parsed() {
+    let (_server, client) = start();
+    let start = datetime("2023-05-17 00:00:00");
+    let end = datetime("2023-05-18 00:00:00");
+
+    let weather = client.get_weather_data(501, start, end).unwrap();
+    assert_eq!(weather.len(), 1);
+    assert_eq!(weather[0].barometer, Some(1013.2));
+    assert_eq!(weather[0].solar_radiation, None);
+
+    let coincidences = client
+        .get_coincidences(StationSelection::Network, 2, start, end)
+        .unwrap();
+    assert_eq!(coincidences.len(), 1);
+    assert_eq!(coincidences[0].events.len(), 2);
+    assert_eq!(coincidences[0].events[1].station_number, 7001);
+    assert_eq!(coincidences[0].events[1].event.number_of_detectors(), 2);
+}
+
+#[test]
+fn singles_are_parsed() {
+    let (_server, client) = start();
+    let start = datetime("2023-05-17 00:00:00");
+    let end = datetime("2023-05-18 00:00:00");
+
+    let singles = client.get_singles_data(501, start, end).unwrap();
+    assert_eq!(singles.len(), 2);
+    assert_eq!(singles[0].high.detector_4(), Some(&79));
+    assert_eq!(singles[1].low.detector_2(), None);
+}
+
+#[test]
+fn singles_ranges_exclude_their_end() {
+    let (_server, client) = start();
+    let start = datetime("2023-05-17 00:00:00");
+
+    // Station 502 only has singles on the 18th
+    let midnight = datetime("2023-05-18 00:00:00");
+    assert!(!client.has_singles_in_range(502, start, midnight).unwrap());
+
+    let after_midnight = datetime("2023-05-18 00:00:01");
+    assert!(client
+        .has_singles_in_range(502, start, after_midnight)
+        .unwrap());
+}
+
+#[test]
+fn lightning_is_parsed() {
+    let (_server, client) = start();
+    let start = datetime("2023-05-17 00:00:00");
+    let end = datetime("2023-05-18 00:00:00");
+
+    let strikes = client
+        .get_lightning_data(LightningType::CloudGround, start, end)
+        .unwrap();
+    assert_eq!(strikes.len(), 2);
+    assert_eq!(strikes[1].current, 11.8);
+}
+
+#[test]
+fn chunked_downloads_are_stitched_in_order() {
+    let (server, client) = start();
+
+    let events = ChunkedDownloader::new()
+        .client(client)
+        .window(chrono::Duration::hours(1))
+        .get_event_data(
+            501,
+            datetime("2023-05-17 00:00:00"),
+            datetime("2023-05-18 01:00:00"),
+        )
+        .unwrap();
+
+    let timestamps: Vec<i64> = events.iter().map(|x| x.timestamp().timestamp()).collect();
+    assert_eq!(timestamps, vec![1684281612, 1684283400, 1684368005]);
+    assert_eq!(server.requests().len(), 25);
+}
+
+#[test]
+fn malformed_rows_carry_their_line_number() {
+    let (_server, client) = start();
+    let start = datetime("2023-05-17 00:00:00");
+    let end = datetime("2023-05-18 00:00:00");
+
+    let error = client.get_weather_data(502, start, end).unwrap_err();
+    assert_eq!(error.line(), Some(4));
+
+    let coincidences = "# Coincidences\n1\t501\t2023-05-17\n";
+    let error = hisparc::data::Coincidence::from_tsv_lines(coincidences.lines()).unwrap_err();
+    assert_eq!(error.line(), Some(2));
+}
+
+#[test]
+fn unexpected_json_is_the_same_error_with_or_without_a_cache() {
+    let dir = temp_dir("json-error");
+    let server = MockServer::start(fixtures()).unwrap();
+
+    let live = server.client().unwrap();
+    let cached = server
+        .client_builder()
+        .cache(ResponseCache::new(&dir))
+        .build()
+        .unwrap();
+
+    for client in [live, cached] {
+        let error = client.get_station_info(9000, None).unwrap_err();
+        assert!(matches!(error, HisparcError::Json { .. }), "{:?}", error);
+        assert!(error.to_string().contains("station/9000/"));
+    }
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
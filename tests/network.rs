@@ -0,0 +1,30 @@
+use hisparc::api::Network;
+use hisparc::mock::MockServer;
+
+mod common;
+use common::fixtures;
+
+#[test]
+fn network_is_assembled_from_every_level() {
+    let server = MockServer::start(fixtures()).unwrap();
+    let network = server.client().unwrap().get_network().unwrap();
+
+    assert_eq!(network.countries.len(), 2);
+    assert!(network.country(20000).unwrap().clusters.is_empty());
+
+    let stations: Vec<u32> = network.stations().map(|x| x.station.number).collect();
+    assert_eq!(stations, vec![501, 502, 7001]);
+
+    let path = network.station(501).unwrap();
+    assert_eq!(path.subcluster.name, "Science Park");
+    assert_eq!(path.cluster.name, "Amsterdam");
+    assert_eq!(path.country.name, "Netherlands");
+
+    assert_eq!(network.cluster_of_station(7001).unwrap().number, 7000);
+    assert_eq!(network.cluster_of_subcluster(500).unwrap().number, 0);
+    assert_eq!(network.country_of_cluster(7000).unwrap().number, 0);
+    assert!(network.cluster_of_station(9999).is_none());
+
+    let json = network.to_json().unwrap();
+    assert_eq!(Network::from_json(&json).unwrap(), network);
+}
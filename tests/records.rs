@@ -0,0 +1,59 @@
+use hisparc::data::{LightningStrike, SinglesRecord};
+
+#[test]
+fn singles_rows_are_parsed_per_detector() {
+    let row = "2023-05-17\t00:00:01\t1684281601\t1130\t-1\t-1\t-1\t1143\t88\t-999\t77";
+    let record = SinglesRecord::from_tsv(row).unwrap();
+
+    assert_eq!(record.timestamp.timestamp(), 1684281601);
+    assert_eq!(record.low.detector_1(), Some(&1130));
+    assert_eq!(record.high.detector_1(), None);
+    assert_eq!(record.low.detector_2(), None);
+    assert_eq!(record.low.detector_3(), Some(&1143));
+    assert_eq!(record.high.detector_3(), Some(&88));
+    assert_eq!(record.low.detector_4(), None);
+    assert_eq!(record.high.detector_4(), Some(&77));
+}
+
+#[test]
+fn lightning_rows_are_parsed() {
+    let row = "2023-05-17\t14:02:11\t1684332131\t482913044\t52.3491\t4.9563\t-23.4";
+    let strike = LightningStrike::from_tsv(row).unwrap();
+
+    assert_eq!(strike.timestamp.timestamp(), 1684332131);
+    assert_eq!(strike.timestamp.timestamp_subsec_nanos(), 482913044);
+    assert_eq!(strike.latitude, 52.3491);
+    assert_eq!(strike.longitude, 4.9563);
+    assert_eq!(strike.current, -23.4);
+}
+
+#[test]
+fn short_singles_rows_are_errors() {
+    let singles = "2023-05-17\t00:00:00\t1684281600\t1121\t86\t1098\t81";
+    assert!(SinglesRecord::from_tsv(singles).is_err());
+}
+
+#[test]
+fn short_lightning_rows_are_errors() {
+    let lightning = "2023-05-17\t14:02:11\t1684332131\t482913044\t52.3491";
+    assert!(LightningStrike::from_tsv(lightning).is_err());
+}
+
+#[test]
+fn records_survive_a_json_round_trip() {
+    let row = "2023-05-17\t00:00:01\t1684281601\t1130\t-1\t-1\t-1\t1143\t88\t-999\t77";
+    let singles = SinglesRecord::from_tsv(row).unwrap();
+    let json = serde_json::to_string(&singles).unwrap();
+    assert_eq!(
+        serde_json::from_str::<SinglesRecord>(&json).unwrap(),
+        singles
+    );
+
+    let row = "2023-05-17\t14:02:11\t1684332131\t482913044\t52.3491\t4.9563\t-23.4";
+    let strike = LightningStrike::from_tsv(row).unwrap();
+    let json = serde_json::to_string(&strike).unwrap();
+    assert_eq!(
+        serde_json::from_str::<LightningStrike>(&json).unwrap(),
+        strike
+    );
+}
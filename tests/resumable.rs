@@ -0,0 +1,110 @@
+use chrono::Duration;
+use hisparc::data::{ChunkedDownloader, ResumableDownload};
+use hisparc::mock::MockServer;
+use hisparc::HisparcError;
+use std::fs;
+use std::path::Path;
+
+mod common;
+use common::{datetime, fixtures, temp_dir};
+
+fn job(server: &MockServer, station_number: u32, directory: &Path) -> ResumableDownload {
+    let downloader = ChunkedDownloader::new()
+        .client(server.client().unwrap())
+        .window(Duration::hours(6));
+
+    ResumableDownload::new(
+        station_number,
+        datetime("2023-05-17 00:00:00"),
+        datetime("2023-05-18 00:00:00"),
+        directory,
+    )
+    .downloader(downloader)
+}
+
+fn event_requests(server: &MockServer) -> usize {
+    server
+        .requests()
+        .iter()
+        .filter(|x| x.contains("data_type=events"))
+        .count()
+}
+
+fn timestamps(events: &[hisparc::data::Event]) -> Vec<i64> {
+    events.iter().map(|x| x.timestamp().timestamp()).collect()
+}
+
+#[test]
+fn resumed_downloads_fetch_only_the_missing_windows() {
+    let dir = temp_dir("resumable");
+    let server = MockServer::start(fixtures()).unwrap();
+    let download = job(&server, 501, &dir);
+
+    let events = download.run().unwrap();
+    assert_eq!(timestamps(&events), vec![1684281612, 1684283400]);
+    assert_eq!(event_requests(&server), 4);
+
+    // Leave the job as if it was interrupted during the third window: the
+    // last two windows are missing, the third has a .part file and the
+    // checkpoint ends in a torn line
+    let windows = download.windows().unwrap();
+    let checkpoint = dir.join("checkpoint.tsv");
+    let header = fs::read_to_string(&checkpoint)
+        .unwrap()
+        .lines()
+        .next()
+        .unwrap()
+        .to_string();
+
+    let mut lines = vec![header];
+    for (start, end) in &windows[..2] {
+        lines.push(format!("{}\t{}", start.timestamp(), end.timestamp()));
+    }
+    let (start, end) = windows[2];
+    lines.push(format!("{}\t{}", start.timestamp(), end.timestamp() / 1000));
+    fs::write(&checkpoint, lines.join("\n")).unwrap();
+
+    for (start, end) in &windows[2..] {
+        let path = dir.join(format!(
+            "events_{}_{}.tsv",
+            start.timestamp(),
+            end.timestamp()
+        ));
+        fs::remove_file(&path).unwrap();
+    }
+    let part = dir.join(format!(
+        "events_{}_{}.tsv.part",
+        start.timestamp(),
+        end.timestamp()
+    ));
+    fs::write(&part, "<html>half a response").unwrap();
+
+    let server = MockServer::start(fixtures()).unwrap();
+    let resumed = job(&server, 501, &dir).run().unwrap();
+    assert_eq!(timestamps(&resumed), timestamps(&events));
+    assert_eq!(event_requests(&server), 2);
+
+    // Both windows made it into the checkpoint despite the torn line
+    let server = MockServer::start(fixtures()).unwrap();
+    job(&server, 501, &dir).run().unwrap();
+    assert_eq!(event_requests(&server), 0);
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn checkpoints_of_another_job_are_rejected() {
+    let dir = temp_dir("resumable-mismatch");
+    let server = MockServer::start(fixtures()).unwrap();
+    job(&server, 501, &dir).run().unwrap();
+
+    let error = job(&server, 502, &dir).run().unwrap_err();
+    assert!(
+        matches!(error, HisparcError::InvalidInput(_)),
+        "{:?}",
+        error
+    );
+    assert_eq!(event_requests(&server), 4);
+
+    let _ = fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,74 @@
+use hisparc::api::UrlTemplate;
+use std::collections::HashMap;
+
+const NUM_EVENTS: &str = "station/{station_number}/num_events/{year}/{month}/{day}/{hour}/";
+
+fn values(pairs: &[(&'static str, &str)]) -> HashMap<&'static str, String> {
+    pairs.iter().map(|(k, v)| (*k, v.to_string())).collect()
+}
+
+#[test]
+fn trailing_date_parameters_are_optional() {
+    let template = UrlTemplate::parse(NUM_EVENTS).unwrap();
+
+    assert_eq!(
+        template.required_parameters().collect::<Vec<_>>(),
+        vec!["station_number"]
+    );
+    assert_eq!(
+        template.optional_parameters().collect::<Vec<_>>(),
+        vec!["year", "month", "day", "hour"]
+    );
+
+    let all = values(&[
+        ("station_number", "501"),
+        ("year", "2023"),
+        ("month", "5"),
+        ("day", "17"),
+        ("hour", "0"),
+    ]);
+    assert_eq!(
+        template.expand(&all).unwrap(),
+        "station/501/num_events/2023/5/17/0/"
+    );
+
+    let month = values(&[("station_number", "501"), ("year", "2023"), ("month", "5")]);
+    assert_eq!(
+        template.expand(&month).unwrap(),
+        "station/501/num_events/2023/5/"
+    );
+
+    let none = values(&[("station_number", "501")]);
+    assert_eq!(template.expand(&none).unwrap(), "station/501/num_events/");
+}
+
+#[test]
+fn invalid_values_are_rejected() {
+    let template = UrlTemplate::parse(NUM_EVENTS).unwrap();
+
+    let gap = values(&[("station_number", "501"), ("year", "2023"), ("day", "17")]);
+    assert!(template.expand(&gap).is_err());
+
+    let missing = values(&[("year", "2023")]);
+    let error = template.expand(&missing).unwrap_err();
+    assert!(error.to_string().contains("missing station_number"));
+
+    let unknown = values(&[("station_number", "501"), ("cluster", "Amsterdam")]);
+    assert!(template.expand(&unknown).is_err());
+}
+
+#[test]
+fn values_are_percent_encoded_and_braces_escaped() {
+    let template = UrlTemplate::parse("clusters/{{all}}/{name}/").unwrap();
+    assert_eq!(template.parameters().collect::<Vec<_>>(), vec!["name"]);
+
+    let name = values(&[("name", "Science Park/Amsterdam")]);
+    assert_eq!(
+        template.expand(&name).unwrap(),
+        "clusters/{all}/Science%20Park%2FAmsterdam/"
+    );
+
+    assert!(UrlTemplate::parse("station/{station_number/").is_err());
+    assert!(UrlTemplate::parse("station/}/").is_err());
+    assert!(UrlTemplate::parse("station/{}/").is_err());
+}